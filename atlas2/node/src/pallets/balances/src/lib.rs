@@ -33,14 +33,22 @@ use frame_support::{
 use frame_system::{ensure_signed, pallet_prelude::*};
 use scale_info::TypeInfo;
 use sp_runtime::{
-    traits::{AtLeast32BitUnsigned, CheckedSub, StaticLookup, Zero},
+    traits::{AtLeast32BitUnsigned, Bounded, CheckedSub, StaticLookup, Zero},
     DispatchError as RtDispatchError, RuntimeDebug,
 };
 use sp_std::prelude::*;
 
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 // Re-export pallet items so that they can be accessed from the crate namespace.
 pub use pallet::*;
 
+/// The lock placed on the whole balance of a frozen account.
+const FREEZE_ID: LockIdentifier = *b"atlfreez";
+
 /// The account type, used to differentiate between different kinds of accounts
 /// on the public ledger.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
@@ -83,6 +91,31 @@ impl<Balance: Default> Default for AccountInfo<Balance> {
     }
 }
 
+/// Reports whether a gateway account still has outstanding obligations (e.g. pending shield
+/// or unshield operations) that would be orphaned if it stopped being a gateway.
+pub trait GatewayObligations<AccountId> {
+    /// Whether `who` has outstanding gateway obligations.
+    fn has_outstanding_obligations(who: &AccountId) -> bool;
+}
+
+impl<AccountId> GatewayObligations<AccountId> for () {
+    fn has_outstanding_obligations(_who: &AccountId) -> bool {
+        false
+    }
+}
+
+/// Provides the `AccountType` of an account to other pallets.
+pub trait AccountTypeProvider<AccountId> {
+    /// The account type of `who`
+    fn account_type(who: &AccountId) -> AccountType;
+}
+
+impl<AccountId> AccountTypeProvider<AccountId> for () {
+    fn account_type(_who: &AccountId) -> AccountType {
+        AccountType::Normal
+    }
+}
+
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
@@ -102,6 +135,22 @@ pub mod pallet {
             + Default
             + Copy
             + MaxEncodedLen;
+        
+        /// The currency performing public transfers, and locking the balance of frozen accounts
+        type Currency: LockableCurrency<Self::AccountId, Balance = Self::Balance>;
+        
+        /// Source of outstanding gateway obligations, consulted before a gateway is demoted
+        type GatewayObligations: GatewayObligations<Self::AccountId>;
+        
+        /// The maximum length of a transfer memo
+        #[pallet::constant]
+        type MaxMemoLength: Get<u32>;
+        
+        /// The origin allowed to freeze and thaw accounts
+        type FreezeOrigin: EnsureOrigin<Self::Origin>;
+        
+        /// The origin allowed to set any account's type, including `Contract` and `Gateway`
+        type AccountTypeAdmin: EnsureOrigin<Self::Origin>;
     }
 
     #[pallet::pallet]
@@ -118,6 +167,18 @@ pub mod pallet {
         AccountInfo<T::Balance>,
         ValueQuery,
     >;
+    
+    /// Accounts frozen by the `FreezeOrigin`, which can neither send nor receive public
+    /// transfers. Their whole balance is also locked, so no other pallet can move it either.
+    #[pallet::storage]
+    #[pallet::getter(fn frozen_accounts)]
+    pub type FrozenAccounts<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        (),
+        OptionQuery,
+    >;
 
     // Events
     #[pallet::event]
@@ -127,6 +188,14 @@ pub mod pallet {
         AccountInfoUpdated(T::AccountId),
         /// Account type was changed
         AccountTypeChanged(T::AccountId, AccountType),
+        /// A public transfer was made
+        PublicTransfer(T::AccountId, T::AccountId, T::Balance),
+        /// A public transfer was made with an attached memo
+        PublicTransferWithMemo(T::AccountId, T::AccountId, T::Balance, BoundedVec<u8, T::MaxMemoLength>),
+        /// An account was frozen
+        AccountFrozen(T::AccountId),
+        /// A frozen account was thawed
+        AccountThawed(T::AccountId),
     }
 
     // Errors
@@ -136,24 +205,118 @@ pub mod pallet {
         AccountTypeChangeNotAllowed,
         /// Invalid account type for this operation
         InvalidAccountType,
+        /// The destination of a transfer is the sender itself
+        CannotTransferToSelf,
+        /// The sender or destination of a transfer is frozen
+        AccountFrozen,
+        /// The account is already frozen
+        AlreadyFrozen,
+        /// The account is not frozen
+        NotFrozen,
+        /// The sender's nonce does not match the expected nonce
+        NonceMismatch,
     }
 
     // Dispatchable functions
     #[pallet::call]
     impl<T: Config> Pallet<T> {
+        /// Transfer `value` from the sender to `dest` on the public ledger
+        ///
+        /// The sender is kept alive. Updates the sender's nonce and `total_sent` and the
+        /// destination's `total_received`. Fails if either account is frozen.
+        #[pallet::weight(10_000)]
+        pub fn transfer(
+            origin: OriginFor<T>,
+            dest: <T::Lookup as StaticLookup>::Source,
+            #[pallet::compact] value: T::Balance,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let dest = T::Lookup::lookup(dest)?;
+            
+            Self::do_transfer(&who, &dest, value)?;
+            
+            Self::deposit_event(Event::PublicTransfer(who, dest, value));
+            Ok(())
+        }
+        
+        /// Transfer `value` from the sender to `dest` with an opaque `memo`, e.g. an exchange's
+        /// reference id
+        ///
+        /// Behaves like `transfer`. The memo is not stored; it is only emitted in
+        /// `PublicTransferWithMemo`.
+        #[pallet::weight(10_000)]
+        pub fn transfer_with_memo(
+            origin: OriginFor<T>,
+            dest: <T::Lookup as StaticLookup>::Source,
+            #[pallet::compact] value: T::Balance,
+            memo: BoundedVec<u8, T::MaxMemoLength>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let dest = T::Lookup::lookup(dest)?;
+            
+            Self::do_transfer(&who, &dest, value)?;
+            
+            Self::deposit_event(Event::PublicTransferWithMemo(who, dest, value, memo));
+            Ok(())
+        }
+        
+        /// Transfer `value` from the sender to `dest` only if the sender's nonce in
+        /// `AccountInfos` equals `expected_nonce`
+        ///
+        /// Gives clients a replay guard independent of the system nonce: the nonce advances on
+        /// every successful transfer, so a resubmitted call fails with `NonceMismatch`.
+        #[pallet::weight(10_000)]
+        pub fn transfer_with_nonce(
+            origin: OriginFor<T>,
+            dest: <T::Lookup as StaticLookup>::Source,
+            #[pallet::compact] value: T::Balance,
+            expected_nonce: u64,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let dest = T::Lookup::lookup(dest)?;
+            
+            ensure!(
+                AccountInfos::<T>::get(&who).nonce == expected_nonce,
+                Error::<T>::NonceMismatch
+            );
+            
+            Self::do_transfer(&who, &dest, value)?;
+            
+            Self::deposit_event(Event::PublicTransfer(who, dest, value));
+            Ok(())
+        }
+        
         /// Set the account type for an account
+        ///
+        /// The `AccountTypeAdmin` may set any type on any account. Otherwise the origin must be
+        /// the account itself, and it may only return to `Normal`.
         #[pallet::weight(10_000)]
         pub fn set_account_type(
             origin: OriginFor<T>,
             account: <T::Lookup as StaticLookup>::Source,
             new_type: AccountType,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
             let account = T::Lookup::lookup(account)?;
             
-            // TODO: Implement proper access control here
-            // For now, only the account itself can change its type
-            ensure!(who == account, Error::<T>::AccountTypeChangeNotAllowed);
+            if let Err(origin) = T::AccountTypeAdmin::try_origin(origin) {
+                // Promotions to privileged types require the admin
+                let who = ensure_signed(origin)?;
+                ensure!(
+                    who == account && new_type == AccountType::Normal,
+                    Error::<T>::AccountTypeChangeNotAllowed
+                );
+            }
+            
+            let current_type = AccountInfos::<T>::get(&account).account_type;
+            ensure!(Self::valid_transition(&current_type, &new_type), Error::<T>::InvalidAccountType);
+            
+            // A gateway cannot step down while it still has pool obligations
+            if current_type == AccountType::Gateway && new_type != AccountType::Gateway {
+                ensure!(
+                    !T::GatewayObligations::has_outstanding_obligations(&account),
+                    Error::<T>::InvalidAccountType
+                );
+            }
             
             AccountInfos::<T>::mutate(&account, |info| {
                 info.account_type = new_type.clone();
@@ -162,6 +325,45 @@ pub mod pallet {
             Self::deposit_event(Event::AccountTypeChanged(account, new_type));
             Ok(())
         }
+        
+        /// Freeze an account, blocking public transfers from and to it
+        ///
+        /// Locks the account's entire balance for all withdraw reasons, so the freeze also
+        /// holds against transfers made through other pallets.
+        #[pallet::weight(10_000)]
+        pub fn freeze_account(
+            origin: OriginFor<T>,
+            account: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            T::FreezeOrigin::ensure_origin(origin)?;
+            let account = T::Lookup::lookup(account)?;
+            
+            ensure!(!Self::is_frozen(&account), Error::<T>::AlreadyFrozen);
+            
+            FrozenAccounts::<T>::insert(&account, ());
+            T::Currency::set_lock(FREEZE_ID, &account, T::Balance::max_value(), WithdrawReasons::all());
+            
+            Self::deposit_event(Event::AccountFrozen(account));
+            Ok(())
+        }
+        
+        /// Thaw a frozen account, allowing public transfers from and to it again
+        #[pallet::weight(10_000)]
+        pub fn thaw_account(
+            origin: OriginFor<T>,
+            account: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            T::FreezeOrigin::ensure_origin(origin)?;
+            let account = T::Lookup::lookup(account)?;
+            
+            ensure!(Self::is_frozen(&account), Error::<T>::NotFrozen);
+            
+            FrozenAccounts::<T>::remove(&account);
+            T::Currency::remove_lock(FREEZE_ID, &account);
+            
+            Self::deposit_event(Event::AccountThawed(account));
+            Ok(())
+        }
     }
 
     // Hooks
@@ -169,6 +371,57 @@ pub mod pallet {
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
         // TODO: Implement hooks
     }
+
+    // Additional implementation for the pallet
+    impl<T: Config> Pallet<T> {
+        /// Transfer `value` from `who` to `dest`, keeping `who` alive, and update both accounts'
+        /// transfer counters.
+        fn do_transfer(who: &T::AccountId, dest: &T::AccountId, value: T::Balance) -> DispatchResult {
+            ensure!(who != dest, Error::<T>::CannotTransferToSelf);
+            ensure!(
+                !Self::is_frozen(who) && !Self::is_frozen(dest),
+                Error::<T>::AccountFrozen
+            );
+            
+            T::Currency::transfer(who, dest, value, ExistenceRequirement::KeepAlive)?;
+            
+            AccountInfos::<T>::mutate(who, |info| {
+                info.nonce = info.nonce.saturating_add(1);
+                info.total_sent = info.total_sent.saturating_add(value);
+            });
+            AccountInfos::<T>::mutate(dest, |info| {
+                info.total_received = info.total_received.saturating_add(value);
+            });
+            
+            Ok(())
+        }
+        
+        /// Whether `who` is frozen.
+        pub fn is_frozen(who: &T::AccountId) -> bool {
+            FrozenAccounts::<T>::contains_key(who)
+        }
+        
+        /// Whether an account may change its type from `from` to `to`.
+        ///
+        /// Normal accounts may become contracts or gateways, and gateways may return to normal
+        /// (subject to having no outstanding obligations). Contracts can never change type, as
+        /// that would orphan their contract state, and gateways cannot become contracts.
+        pub fn valid_transition(from: &AccountType, to: &AccountType) -> bool {
+            match (from, to) {
+                (from, to) if from == to => true,
+                (AccountType::Normal, AccountType::Contract) => true,
+                (AccountType::Normal, AccountType::Gateway) => true,
+                (AccountType::Gateway, AccountType::Normal) => true,
+                _ => false,
+            }
+        }
+    }
+}
+
+impl<T: Config> AccountTypeProvider<T::AccountId> for Pallet<T> {
+    fn account_type(who: &T::AccountId) -> AccountType {
+        AccountInfos::<T>::get(who).account_type
+    }
 }
 
 // TODO: Implement functions to integrate with the shielded pool
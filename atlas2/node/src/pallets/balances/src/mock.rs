@@ -0,0 +1,124 @@
+//! Test runtime for the atlas balances pallet.
+
+use crate as pallet_atlas_balances;
+use crate::*;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, ConstU64, Everything, GenesisBuild},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub type AccountId = u64;
+pub type Balance = u128;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        AtlasBalances: pallet_atlas_balances::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: Balance = 1;
+
+    // Varied by individual tests
+    pub static ObligatedGateways: Vec<AccountId> = vec![];
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ConstU32<50>;
+    type MaxReserves = ConstU32<50>;
+    type ReserveIdentifier = [u8; 8];
+    type Balance = Balance;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+/// Reports the accounts in `ObligatedGateways` as having outstanding obligations.
+pub struct TestObligations;
+impl GatewayObligations<AccountId> for TestObligations {
+    fn has_outstanding_obligations(who: &AccountId) -> bool {
+        ObligatedGateways::get().contains(who)
+    }
+}
+
+impl Config for Test {
+    type Event = Event;
+    type Balance = Balance;
+    type Currency = Balances;
+    type GatewayObligations = TestObligations;
+    type MaxMemoLength = ConstU32<16>;
+    type FreezeOrigin = EnsureRoot<AccountId>;
+    type AccountTypeAdmin = EnsureRoot<AccountId>;
+}
+
+/// The free balance every endowed account starts with.
+pub const INITIAL_BALANCE: Balance = 1_000;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, INITIAL_BALANCE), (2, INITIAL_BALANCE), (3, INITIAL_BALANCE)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+/// The events of this pallet deposited so far.
+pub fn atlas_balances_events() -> Vec<pallet_atlas_balances::Event<Test>> {
+    System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::AtlasBalances(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,137 @@
+//! Tests for the atlas balances pallet.
+
+use crate::{mock::*, Event as AtlasBalancesEvent, *};
+use frame_support::{assert_noop, assert_ok, BoundedVec};
+use sp_runtime::traits::BadOrigin;
+
+#[test]
+fn transfer_moves_funds_and_updates_counters() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AtlasBalances::transfer(Origin::signed(1), 2, 100));
+        
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 100);
+        assert_eq!(AtlasBalances::account_info(1).nonce, 1);
+        assert_eq!(AtlasBalances::account_info(1).total_sent, 100);
+        assert_eq!(AtlasBalances::account_info(2).total_received, 100);
+        assert!(atlas_balances_events().contains(&AtlasBalancesEvent::PublicTransfer(1, 2, 100)));
+    });
+}
+
+#[test]
+fn transfer_to_self_is_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            AtlasBalances::transfer(Origin::signed(1), 1, 100),
+            Error::<Test>::CannotTransferToSelf
+        );
+    });
+}
+
+#[test]
+fn account_type_transitions_follow_the_rules() {
+    new_test_ext().execute_with(|| {
+        // Promotions from `Normal` are allowed
+        assert_ok!(AtlasBalances::set_account_type(Origin::root(), 1, AccountType::Contract));
+        assert_ok!(AtlasBalances::set_account_type(Origin::root(), 2, AccountType::Gateway));
+        assert_ok!(AtlasBalances::set_account_type(Origin::root(), 3, AccountType::Normal));
+        
+        // A contract stays a contract
+        assert_noop!(
+            AtlasBalances::set_account_type(Origin::root(), 1, AccountType::Normal),
+            Error::<Test>::InvalidAccountType
+        );
+        assert_noop!(
+            AtlasBalances::set_account_type(Origin::root(), 1, AccountType::Gateway),
+            Error::<Test>::InvalidAccountType
+        );
+        
+        // A gateway can only step down to `Normal`, and only without obligations
+        assert_noop!(
+            AtlasBalances::set_account_type(Origin::root(), 2, AccountType::Contract),
+            Error::<Test>::InvalidAccountType
+        );
+        ObligatedGateways::set(&vec![2]);
+        assert_noop!(
+            AtlasBalances::set_account_type(Origin::signed(2), 2, AccountType::Normal),
+            Error::<Test>::InvalidAccountType
+        );
+        ObligatedGateways::set(&vec![]);
+        assert_ok!(AtlasBalances::set_account_type(Origin::signed(2), 2, AccountType::Normal));
+        assert_eq!(AtlasBalances::account_info(2).account_type, AccountType::Normal);
+    });
+}
+
+#[test]
+fn a_frozen_account_cannot_transfer_until_thawed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(AtlasBalances::freeze_account(Origin::signed(1), 1), BadOrigin);
+        assert_ok!(AtlasBalances::freeze_account(Origin::root(), 1));
+        assert!(atlas_balances_events().contains(&AtlasBalancesEvent::AccountFrozen(1)));
+        
+        assert_noop!(AtlasBalances::transfer(Origin::signed(1), 2, 100), Error::<Test>::AccountFrozen);
+        assert_noop!(AtlasBalances::transfer(Origin::signed(2), 1, 100), Error::<Test>::AccountFrozen);
+        // The lock holds against the underlying balances pallet as well
+        assert_noop!(
+            Balances::transfer(Origin::signed(1), 2, 100),
+            pallet_balances::Error::<Test>::LiquidityRestrictions
+        );
+        
+        assert_ok!(AtlasBalances::thaw_account(Origin::root(), 1));
+        assert!(atlas_balances_events().contains(&AtlasBalancesEvent::AccountThawed(1)));
+        assert_ok!(AtlasBalances::transfer(Origin::signed(1), 2, 100));
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 100);
+    });
+}
+
+#[test]
+fn only_the_admin_promotes_accounts_to_privileged_types() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            AtlasBalances::set_account_type(Origin::signed(1), 1, AccountType::Gateway),
+            Error::<Test>::AccountTypeChangeNotAllowed
+        );
+        assert_noop!(
+            AtlasBalances::set_account_type(Origin::signed(2), 1, AccountType::Normal),
+            Error::<Test>::AccountTypeChangeNotAllowed
+        );
+        
+        assert_ok!(AtlasBalances::set_account_type(Origin::root(), 1, AccountType::Gateway));
+        assert_eq!(AtlasBalances::account_info(1).account_type, AccountType::Gateway);
+        assert!(atlas_balances_events()
+            .contains(&AtlasBalancesEvent::AccountTypeChanged(1, AccountType::Gateway)));
+    });
+}
+
+#[test]
+fn a_transfer_memo_round_trips_through_the_event() {
+    new_test_ext().execute_with(|| {
+        let memo: BoundedVec<u8, <Test as Config>::MaxMemoLength> = b"invoice-42".to_vec().try_into().unwrap();
+        
+        assert_ok!(AtlasBalances::transfer_with_memo(Origin::signed(1), 2, 100, memo.clone()));
+        
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 100);
+        assert!(atlas_balances_events()
+            .contains(&AtlasBalancesEvent::PublicTransferWithMemo(1, 2, 100, memo)));
+        // Memos longer than `MaxMemoLength` cannot even be constructed
+        assert!(BoundedVec::<u8, <Test as Config>::MaxMemoLength>::try_from(vec![0u8; 17]).is_err());
+    });
+}
+
+#[test]
+fn a_stale_nonce_is_rejected_and_the_expected_one_advances() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(AtlasBalances::transfer_with_nonce(Origin::signed(1), 2, 100, 0));
+        assert_eq!(AtlasBalances::account_info(1).nonce, 1);
+        
+        // Replaying the same call is rejected
+        assert_noop!(
+            AtlasBalances::transfer_with_nonce(Origin::signed(1), 2, 100, 0),
+            Error::<Test>::NonceMismatch
+        );
+        
+        assert_ok!(AtlasBalances::transfer_with_nonce(Origin::signed(1), 2, 100, 1));
+        assert_eq!(AtlasBalances::account_info(1).nonce, 2);
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 200);
+    });
+}
@@ -0,0 +1,30 @@
+//! # Shielded Pool Runtime API
+//!
+//! Runtime API definition used by operators and indexers to monitor the shielded pool
+//! without reading raw storage.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_shielded_pool::{Commitment, Nullifier};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+    /// Queries over the shielded pool pallet.
+    pub trait ShieldedPoolApi<BlockNumber>
+    where
+        BlockNumber: Codec,
+    {
+        /// The number of commitments appended in the last finalized block.
+        fn commitments_per_block() -> u32;
+
+        /// The commitments appended in blocks `from..=to` with their note ciphertexts, for
+        /// wallets to trial-decrypt. The range is capped at `MaxNoteScanWindow` blocks from
+        /// `from`.
+        fn notes_in_range(from: BlockNumber, to: BlockNumber) -> Vec<(Commitment, Vec<u8>)>;
+
+        /// Whether `nullifier` has been published, so wallets can drop spent notes from their
+        /// local note set.
+        fn nullifier_spent(nullifier: Nullifier) -> bool;
+    }
+}
@@ -0,0 +1,56 @@
+//! Benchmarks for the shielded pool pallet.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+
+/// The amount each benchmark unshielding request pays out.
+fn request_amount<T: Config>() -> T::Balance {
+    T::Currency::minimum_balance().saturating_mul(10u32.into())
+}
+
+/// Queue one request per account for `n` accounts, each spending a recorded nullifier, and
+/// back them with the shielded reserve.
+fn queue_requests<T: Config>(n: u32) {
+    let amount = request_amount::<T>();
+    for i in 0 .. n {
+        let destination: T::AccountId = account("destination", i, SEED);
+        let mut nullifier = [0u8; 32];
+        nullifier[..4].copy_from_slice(&i.to_le_bytes());
+        let nullifier = Nullifier(nullifier);
+        Nullifiers::<T>::insert(&nullifier, frame_system::Pallet::<T>::block_number());
+        UnshieldingRequests::<T>::insert(&destination, vec![UnshieldRequest {
+            amount,
+            destination: destination.clone(),
+            nullifier,
+            proof: Proof(Vec::new()),
+        }]);
+    }
+
+    let total = amount.saturating_mul(n.into());
+    TotalShielded::<T>::put(total);
+    T::Currency::make_free_balance_be(
+        &Pallet::<T>::account_id(),
+        total.saturating_add(T::Currency::minimum_balance()),
+    );
+}
+
+benchmarks! {
+    process_unshielding_batch {
+        // The batch size, every slot of which pays out a request
+        let n in 1 .. T::UnshieldingBatchSize::get();
+
+        PoolParameters::<T>::put(PoolParams { unshielding_batch_size: n });
+        queue_requests::<T>(n);
+    }: _(RawOrigin::Root)
+    verify {
+        assert_eq!(UnshieldingRequests::<T>::iter().count(), 0);
+        assert!(TotalShielded::<T>::get().is_zero());
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::mock::ExtBuilder::default().build(), crate::mock::Test);
+}
@@ -0,0 +1,289 @@
+//! Test runtime for the shielded pool pallet.
+
+use crate as pallet_shielded_pool;
+use crate::*;
+use codec::Encode;
+use frame_support::{
+    dispatch::{DispatchResult, DispatchResultWithPostInfo},
+    parameter_types,
+    traits::{ConstU32, ConstU64, Currency, Everything, GenesisBuild, Hooks, OnUnbalanced},
+    BoundedVec, PalletId,
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub type AccountId = u64;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        AtlasBalances: pallet_atlas_balances::{Pallet, Call, Storage, Event<T>},
+        ShieldedPool: pallet_shielded_pool::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = BlockNumber;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ConstU32<50>;
+    type MaxReserves = ConstU32<50>;
+    type ReserveIdentifier = [u8; 8];
+    type Balance = Balance;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+impl pallet_atlas_balances::Config for Test {
+    type Event = Event;
+    type Balance = Balance;
+    type Currency = Balances;
+    type GatewayObligations = ShieldedPool;
+    type MaxMemoLength = ConstU32<32>;
+    type FreezeOrigin = EnsureRoot<AccountId>;
+    type AccountTypeAdmin = EnsureRoot<AccountId>;
+}
+
+parameter_types! {
+    pub const ShieldedPoolPalletId: PalletId = PalletId(*b"atl/shld");
+    pub const MaxMerkleTreeSize: u32 = 16;
+    pub const UnshieldingBatchSize: u32 = 2;
+    pub const RootHistoryDepth: u32 = 3;
+    pub const MaxInputs: u32 = 2;
+    pub const MaxOutputs: u32 = 2;
+    pub const MaxCiphertextLength: u32 = 64;
+    pub const MaxNoteScanWindow: BlockNumber = 10;
+
+    // Varied by individual tests
+    pub static Transparent: bool = false;
+    pub static RequireGateway: bool = false;
+    pub static ShieldFee: Balance = 0;
+    pub static ProvenInputs: Option<Vec<u8>> = None;
+}
+
+/// Accepts what `MockVerifier` accepts, but once `ProvenInputs` is set only proofs over exactly
+/// those public inputs, standing in for a proof bound to its statement.
+pub struct TestVerifier;
+impl ProofVerifier for TestVerifier {
+    fn verify(vk: &[u8], public_inputs: &[u8], proof: &[u8]) -> bool {
+        MockVerifier::verify(vk, public_inputs, proof) &&
+            ProvenInputs::get().map_or(true, |inputs| inputs == public_inputs)
+    }
+}
+
+/// The account collecting shield fees.
+pub const FEE_POT: AccountId = 99;
+
+/// Pays shield fees into `FEE_POT`.
+pub struct ToFeePot;
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for ToFeePot {
+    fn on_nonzero_unbalanced(fee: NegativeImbalanceOf<Test>) {
+        Balances::resolve_creating(&FEE_POT, fee);
+    }
+}
+
+impl Config for Test {
+    type Event = Event;
+    type Balance = Balance;
+    type Currency = Balances;
+    type PalletId = ShieldedPoolPalletId;
+    type MaxMerkleTreeSize = MaxMerkleTreeSize;
+    type UnshieldingBatchSize = UnshieldingBatchSize;
+    type Verifier = TestVerifier;
+    type RootHistoryDepth = RootHistoryDepth;
+    type Transparent = Transparent;
+    type MaxInputs = MaxInputs;
+    type MaxOutputs = MaxOutputs;
+    type MaxCiphertextLength = MaxCiphertextLength;
+    type MaxNoteScanWindow = MaxNoteScanWindow;
+    type AccountTypes = AtlasBalances;
+    type RequireGateway = RequireGateway;
+    type ShieldFee = ShieldFee;
+    type FeeDestination = ToFeePot;
+    type WeightInfo = ();
+}
+
+/// The free balance every endowed account starts with.
+pub const INITIAL_BALANCE: Balance = 10_000;
+
+pub struct ExtBuilder {
+    endowed: Vec<AccountId>,
+}
+
+impl Default for ExtBuilder {
+    fn default() -> Self {
+        Self { endowed: vec![1, 2, 3, 4] }
+    }
+}
+
+impl ExtBuilder {
+    pub fn transparent(self, transparent: bool) -> Self {
+        Transparent::set(&transparent);
+        self
+    }
+
+    pub fn require_gateway(self, require: bool) -> Self {
+        RequireGateway::set(&require);
+        self
+    }
+
+    pub fn shield_fee(self, fee: Balance) -> Self {
+        ShieldFee::set(&fee);
+        self
+    }
+
+    pub fn build(self) -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+        pallet_balances::GenesisConfig::<Test> {
+            balances: self.endowed.iter().map(|who| (*who, INITIAL_BALANCE)).collect(),
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        let mut ext = sp_io::TestExternalities::new(storage);
+        ext.execute_with(|| {
+            System::set_block_number(1);
+            for circuit in [SHIELD_CIRCUIT, UNSHIELD_CIRCUIT, TRANSFER_CIRCUIT] {
+                VerifyingKeys::<Test>::insert(circuit, b"vk".to_vec());
+            }
+        });
+        ext
+    }
+
+    pub fn build_and_execute(self, test: impl FnOnce()) {
+        self.build().execute_with(test);
+    }
+}
+
+/// Finalize the current block and initialize the next ones up to block `n`.
+pub fn run_to_block(n: BlockNumber) {
+    while System::block_number() < n {
+        ShieldedPool::on_finalize(System::block_number());
+        System::set_block_number(System::block_number() + 1);
+        ShieldedPool::on_initialize(System::block_number());
+    }
+}
+
+/// A proof the mock verifier accepts.
+pub fn valid_proof() -> Proof {
+    Proof(MockVerifier::MAGIC.to_vec())
+}
+
+/// A commitment distinguished by `seed`.
+pub fn commitment(seed: u8) -> Commitment {
+    Commitment([seed; 32])
+}
+
+/// A nullifier distinguished by `seed`.
+pub fn nullifier(seed: u8) -> Nullifier {
+    Nullifier([seed; 32])
+}
+
+/// A ciphertext distinguished by `seed`.
+pub fn ciphertext(seed: u8) -> BoundedVec<u8, MaxCiphertextLength> {
+    BoundedVec::truncate_from(vec![seed; 8])
+}
+
+/// A cleartext note of `owner` worth `value`.
+pub fn note(owner: AccountId, value: Balance, salt: u8) -> Note<AccountId, Balance> {
+    Note { value, owner, salt: [salt; 32] }
+}
+
+/// The proof standing in for `note` in transparent mode.
+pub fn note_proof(note: &Note<AccountId, Balance>) -> Proof {
+    Proof(note.encode())
+}
+
+/// Shield `amount` from `who` under the commitment distinguished by `seed`.
+pub fn shield(who: AccountId, amount: Balance, seed: u8) -> DispatchResultWithPostInfo {
+    ShieldedPool::shield(Origin::signed(who), amount, commitment(seed), ciphertext(seed), valid_proof())
+}
+
+/// Shield `note` from `who` in transparent mode.
+pub fn shield_note(who: AccountId, note: &Note<AccountId, Balance>) -> DispatchResultWithPostInfo {
+    let commitment = ShieldedPool::note_commitment(note);
+    ShieldedPool::shield(Origin::signed(who), note.value, commitment, ciphertext(0), note_proof(note))
+}
+
+/// Request to unshield `amount` to `who` by opening `note` in transparent mode.
+pub fn unshield_note(who: AccountId, amount: Balance, note: &Note<AccountId, Balance>) -> DispatchResult {
+    let nullifier = ShieldedPool::note_nullifier(&ShieldedPool::note_commitment(note));
+    ShieldedPool::request_unshield(Origin::signed(who), amount, who, nullifier, [0; 32], note_proof(note))
+}
+
+/// Finalize the current block and request to unshield `amount` to `who` against the root
+/// that block finalized.
+pub fn request_unshield(who: AccountId, amount: Balance, seed: u8) -> DispatchResult {
+    run_to_block(System::block_number() + 1);
+    unshield(who, amount, seed)
+}
+
+/// Request to unshield `amount` to `who` against the current root, spending the nullifier
+/// distinguished by `seed`.
+pub fn unshield(who: AccountId, amount: Balance, seed: u8) -> DispatchResult {
+    ShieldedPool::request_unshield(
+        Origin::signed(who),
+        amount,
+        who,
+        nullifier(seed),
+        ShieldedPool::merkle_root(),
+        valid_proof(),
+    )
+}
+
+/// The events of this pallet deposited so far.
+pub fn pool_events() -> Vec<pallet_shielded_pool::Event<Test>> {
+    System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::ShieldedPool(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
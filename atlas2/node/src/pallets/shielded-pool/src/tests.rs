@@ -0,0 +1,689 @@
+//! Tests for the shielded pool pallet.
+
+use crate::{mock::*, Event as PoolEvent, *};
+use frame_support::{assert_noop, assert_ok};
+
+#[test]
+fn shield_moves_funds_into_the_reserve_and_commits_the_note() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(ShieldedPool::account_id()), 100);
+        assert_eq!(ShieldedPool::total_shielded(), 100);
+        assert_eq!(ShieldedPool::commitments(commitment(1)), Some(1));
+        assert_eq!(ShieldedPool::commitment_index(commitment(1)), Some(0));
+        assert!(pool_events().contains(&PoolEvent::Shielded(1, 100)));
+    });
+}
+
+#[test]
+fn shield_with_an_invalid_proof_is_rejected() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_noop!(
+            ShieldedPool::shield(Origin::signed(1), 100, commitment(1), ciphertext(1), Proof(b"bogus".to_vec())),
+            Error::<Test>::InvalidProof
+        );
+    });
+}
+
+#[test]
+fn resubmitting_a_shield_is_rejected_and_refunds_its_weight() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        run_to_block(3);
+        
+        let resubmitted = shield(1, 100, 1).unwrap_err();
+        assert_eq!(resubmitted.error, Error::<Test>::CommitmentAlreadyExists.into());
+        assert_eq!(
+            resubmitted.post_info.actual_weight,
+            Some(<Test as frame_system::Config>::DbWeight::get().reads(2))
+        );
+        
+        // Nothing moved and the original inclusion block is kept
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 100);
+        assert_eq!(ShieldedPool::total_shielded(), 100);
+        assert_eq!(ShieldedPool::commitments(commitment(1)), Some(1));
+    });
+}
+
+#[test]
+fn unshielding_more_than_the_note_holds_is_rejected() {
+    ExtBuilder::default().transparent(true).build_and_execute(|| {
+        let spent = note(1, 100, 1);
+        assert_ok!(shield_note(1, &spent));
+        assert_ok!(shield_note(2, &note(2, 200, 2)));
+        
+        // The pool holds enough, but the note does not
+        assert_noop!(unshield_note(1, 150, &spent), Error::<Test>::InvalidUnshield);
+        assert_ok!(unshield_note(1, 100, &spent));
+    });
+}
+
+#[test]
+fn a_gateway_with_pending_unshields_cannot_be_demoted() {
+    ExtBuilder::default().require_gateway(true).build_and_execute(|| {
+        assert_ok!(AtlasBalances::set_account_type(Origin::root(), 1, AccountType::Gateway));
+        assert_ok!(shield(1, 200, 1));
+        assert_ok!(request_unshield(1, 100, 1));
+        
+        assert_noop!(
+            AtlasBalances::set_account_type(Origin::signed(1), 1, AccountType::Normal),
+            pallet_atlas_balances::Error::<Test>::InvalidAccountType
+        );
+        
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert!(ShieldedPool::unshielding_requests(1).is_empty());
+        assert_ok!(AtlasBalances::set_account_type(Origin::signed(1), 1, AccountType::Normal));
+    });
+}
+
+#[test]
+fn writing_down_the_pool_bounds_later_unshields() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        
+        assert_noop!(
+            ShieldedPool::write_down_pool(Origin::root(), 201),
+            Error::<Test>::ShieldedPoolUnderflow
+        );
+        assert_ok!(ShieldedPool::write_down_pool(Origin::root(), 150));
+        assert_eq!(ShieldedPool::total_shielded(), 50);
+        assert!(pool_events().contains(&PoolEvent::PoolWrittenDown(150)));
+        
+        run_to_block(2);
+        assert_noop!(unshield(1, 100, 1), Error::<Test>::InvalidUnshield);
+        assert_ok!(unshield(1, 50, 1));
+    });
+}
+
+#[test]
+fn set_pool_params_overrides_the_batch_size() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_noop!(
+            ShieldedPool::set_pool_params(Origin::root(), PoolParams { unshielding_batch_size: 0 }),
+            Error::<Test>::InvalidPoolParams
+        );
+        assert_ok!(ShieldedPool::set_pool_params(Origin::root(), PoolParams { unshielding_batch_size: 1 }));
+        assert_eq!(ShieldedPool::unshielding_batch_size(), 1);
+        
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        assert_ok!(request_unshield(1, 50, 1));
+        assert_ok!(request_unshield(2, 50, 2));
+        
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        
+        let pending: usize = UnshieldingRequests::<Test>::iter().map(|(_, requests)| requests.len()).sum();
+        assert_eq!(pending, 1);
+        assert_eq!(ShieldedPool::total_shielded(), 150);
+    });
+}
+
+#[test]
+fn two_notes_in_one_block_finalize_a_single_root() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        run_to_block(2);
+        
+        let finalized: Vec<_> = pool_events()
+            .into_iter()
+            .filter(|event| matches!(event, PoolEvent::MerkleRootFinalized(..)))
+            .collect();
+        assert_eq!(finalized, vec![PoolEvent::MerkleRootFinalized(ShieldedPool::merkle_root(), 1)]);
+        assert_eq!(ShieldedPool::root_history().into_inner(), vec![(ShieldedPool::merkle_root(), 1)]);
+        assert_eq!(ShieldedPool::commitments_per_block(), 2);
+    });
+}
+
+#[test]
+fn force_remove_commitment_zeroes_the_leaf_and_updates_the_root() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        run_to_block(2);
+        let root_before = ShieldedPool::merkle_root();
+        
+        assert_ok!(ShieldedPool::force_remove_commitment(Origin::root(), commitment(1)));
+        
+        assert_ne!(ShieldedPool::merkle_root(), root_before);
+        assert_eq!(ShieldedPool::commitments(commitment(1)), None);
+        assert_eq!(ShieldedPool::commitment_index(commitment(1)), None);
+        assert!(ShieldedPool::encrypted_notes(commitment(1)).is_none());
+        assert_eq!(ShieldedPool::root_history().into_inner(), vec![(ShieldedPool::merkle_root(), 2)]);
+        assert!(!ShieldedPool::is_known_root(root_before));
+        assert!(pool_events().contains(&PoolEvent::CommitmentRemoved(commitment(1))));
+        
+        // The other note is untouched
+        assert_eq!(ShieldedPool::commitment_index(commitment(2)), Some(1));
+        assert_noop!(
+            ShieldedPool::force_remove_commitment(Origin::root(), commitment(1)),
+            Error::<Test>::CommitmentNotFound
+        );
+    });
+}
+
+#[test]
+fn notes_remain_spendable_against_the_root_left_by_force_remove_commitment() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        run_to_block(2);
+        let root_before = ShieldedPool::merkle_root();
+        
+        assert_ok!(ShieldedPool::force_remove_commitment(Origin::root(), commitment(1)));
+        
+        // Proofs against the earlier root are rejected, proofs against the new one succeed
+        assert_noop!(
+            ShieldedPool::request_unshield(
+                Origin::signed(2),
+                100,
+                2,
+                nullifier(2),
+                root_before,
+                valid_proof(),
+            ),
+            Error::<Test>::UnknownRoot
+        );
+        assert_ok!(unshield(2, 100, 2));
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn shielding_more_than_the_free_balance_fails_without_a_commitment() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 400, 1));
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 400);
+        
+        assert_noop!(shield(2, INITIAL_BALANCE + 1, 2), Error::<Test>::InsufficientPublicBalance);
+        assert_eq!(ShieldedPool::commitments(commitment(2)), None);
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn a_pool_shortfall_processes_a_partial_batch() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        assert_ok!(request_unshield(1, 100, 1));
+        assert_ok!(request_unshield(2, 100, 2));
+        
+        // Only one of the two requests is still backed
+        assert_ok!(ShieldedPool::write_down_pool(Origin::root(), 50));
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        
+        assert_eq!(ShieldedPool::total_shielded(), 50);
+        let paid = [1, 2].iter().filter(|who| Balances::free_balance(**who) == INITIAL_BALANCE).count();
+        assert_eq!(paid, 1);
+        let pending: usize = UnshieldingRequests::<Test>::iter().map(|(_, requests)| requests.len()).sum();
+        assert_eq!(pending, 1);
+    });
+}
+
+#[test]
+fn processing_or_cancelling_the_last_request_removes_the_queue() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 200, 1));
+        assert_ok!(request_unshield(1, 100, 1));
+        assert_ok!(unshield(2, 50, 2));
+        
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert!(!UnshieldingRequests::<Test>::contains_key(1));
+        assert!(!UnshieldingRequests::<Test>::contains_key(2));
+        
+        assert_ok!(unshield(3, 50, 3));
+        assert_ok!(ShieldedPool::cancel_unshield_request(Origin::signed(3), nullifier(3)));
+        assert!(!UnshieldingRequests::<Test>::contains_key(3));
+    });
+}
+
+#[test]
+fn commitments_per_block_counts_the_last_finalized_block() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        assert_ok!(shield(3, 100, 3));
+        assert_eq!(CommitmentsThisBlock::<Test>::get(), 3);
+        
+        run_to_block(2);
+        assert_eq!(ShieldedPool::commitments_per_block(), 3);
+        assert_eq!(CommitmentsThisBlock::<Test>::get(), 0);
+        
+        assert_ok!(shield(4, 100, 4));
+        run_to_block(3);
+        assert_eq!(ShieldedPool::commitments_per_block(), 1);
+        
+        // A block without commitments resets the count
+        run_to_block(4);
+        assert_eq!(ShieldedPool::commitments_per_block(), 0);
+    });
+}
+
+#[test]
+fn shielding_conserves_public_balance_plus_total_shielded() {
+    ExtBuilder::default().build_and_execute(|| {
+        let issuance = Balances::total_issuance();
+        let public = |who| Balances::free_balance(who);
+        
+        assert_ok!(shield(1, 300, 1));
+        assert_ok!(shield(2, 200, 2));
+        
+        assert_eq!(public(1) + public(2) + ShieldedPool::total_shielded(), 2 * INITIAL_BALANCE);
+        assert_eq!(Balances::total_issuance(), issuance);
+        assert_ok!(ShieldedPool::do_try_state());
+        
+        // Unshielding moves the value back out without creating any
+        assert_ok!(request_unshield(1, 300, 1));
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(public(1) + public(2) + ShieldedPool::total_shielded(), 2 * INITIAL_BALANCE);
+        assert_eq!(Balances::total_issuance(), issuance);
+        assert_ok!(ShieldedPool::do_try_state());
+    });
+}
+
+#[test]
+fn the_merkle_root_matches_hand_computed_roots_for_two_and_four_leaves() {
+    use sp_runtime::traits::{BlakeTwo256, Hash};
+    
+    let hash = |left: [u8; 32], right: [u8; 32]| BlakeTwo256::hash(&[left, right].concat()).0;
+    let leaf = |seed| commitment(seed).0;
+    
+    ExtBuilder::default().build_and_execute(|| {
+        // Sixteen leaves make a tree of depth four, padded with empty subtrees
+        assert_eq!(ShieldedPool::merkle_tree_depth(), 4);
+        let empty_1 = hash([0; 32], [0; 32]);
+        let empty_2 = hash(empty_1, empty_1);
+        let empty_3 = hash(empty_2, empty_2);
+        
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        let two_leaves = hash(hash(hash(hash(leaf(1), leaf(2)), empty_1), empty_2), empty_3);
+        assert_eq!(ShieldedPool::merkle_root(), two_leaves);
+        
+        assert_ok!(shield(3, 100, 3));
+        assert_ok!(shield(4, 100, 4));
+        let four_leaves =
+            hash(hash(hash(hash(leaf(1), leaf(2)), hash(leaf(3), leaf(4))), empty_2), empty_3);
+        assert_eq!(ShieldedPool::merkle_root(), four_leaves);
+        assert_eq!(ShieldedPool::merkle_leaf_count(), 4);
+    });
+}
+
+#[test]
+fn roots_age_out_after_root_history_depth_newer_roots() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        run_to_block(2);
+        let first_root = ShieldedPool::merkle_root();
+        assert!(ShieldedPool::is_known_root(first_root));
+        
+        // Each block that appends a commitment finalizes one newer root
+        for seed in 2..=RootHistoryDepth::get() as u8 {
+            assert_ok!(shield(2, 100, seed));
+            run_to_block(System::block_number() + 1);
+        }
+        assert!(ShieldedPool::is_known_root(first_root));
+        
+        assert_ok!(shield(2, 100, 10));
+        run_to_block(System::block_number() + 1);
+        assert!(!ShieldedPool::is_known_root(first_root));
+        assert_eq!(ShieldedPool::root_history().len() as u32, RootHistoryDepth::get());
+        
+        assert_noop!(
+            ShieldedPool::request_unshield(Origin::signed(1), 100, 1, nullifier(1), first_root, valid_proof()),
+            Error::<Test>::UnknownRoot
+        );
+        assert_ok!(unshield(1, 100, 1));
+    });
+}
+
+#[test]
+fn transparent_mode_shields_transfers_and_unshields_end_to_end() {
+    use codec::Encode;
+    
+    ExtBuilder::default().transparent(true).build_and_execute(|| {
+        let shielded = note(1, 100, 1);
+        assert_ok!(shield_note(1, &shielded));
+        let commitment = ShieldedPool::note_commitment(&shielded);
+        assert_eq!(ShieldedPool::transparent_notes(&commitment), Some(shielded.clone()));
+        
+        // Split the note between account 2 and change for account 1
+        let paid = note(2, 60, 2);
+        let change = note(1, 40, 3);
+        let spent = ShieldedPool::note_nullifier(&commitment);
+        assert_ok!(ShieldedPool::transfer_shielded(
+            Origin::signed(1),
+            vec![spent.clone()],
+            vec![ShieldedPool::note_commitment(&paid), ShieldedPool::note_commitment(&change)],
+            vec![ciphertext(2), ciphertext(3)],
+            [0; 32],
+            Proof((vec![shielded.clone()], vec![paid.clone(), change.clone()]).encode()),
+        ));
+        assert!(ShieldedPool::is_spent(&spent));
+        assert_eq!(ShieldedPool::total_shielded(), 100);
+        
+        // The spent note cannot be unshielded, the received one can
+        assert_noop!(unshield_note(1, 100, &shielded), Error::<Test>::NullifierAlreadyExists);
+        assert_ok!(unshield_note(2, 60, &paid));
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 60);
+        assert_eq!(ShieldedPool::total_shielded(), 40);
+        assert!(pool_events().contains(&PoolEvent::Unshielded(2, 60)));
+    });
+}
+
+#[test]
+fn a_batch_visits_exactly_the_batch_size_and_skips_dropped_nullifiers() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 400, 1));
+        for seed in 1..=3 {
+            assert_ok!(request_unshield(1, 100, seed));
+        }
+        
+        // The second request's nullifier was released behind the queue's back
+        Nullifiers::<Test>::remove(nullifier(2));
+        
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 300);
+        assert_eq!(ShieldedPool::unshielding_requests(1).len(), 1);
+        assert_eq!(ShieldedPool::unshielding_requests(1)[0].nullifier, nullifier(3));
+        assert!(pool_events().contains(&PoolEvent::UnshieldingBatchProcessed(UnshieldingBatchSize::get())));
+        
+        // The next batch resumes with the account's remaining request
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 200);
+        assert!(!UnshieldingRequests::<Test>::contains_key(1));
+        assert_eq!(ShieldedPool::unshielding_cursor(), None);
+        assert_eq!(ShieldedPool::total_shielded(), 200);
+        
+        // The reserve is kept alive
+        assert!(Balances::free_balance(ShieldedPool::account_id()) >= 200);
+    });
+}
+
+#[test]
+fn batches_page_through_accounts_by_cursor() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 400, 1));
+        for who in 1..=4 {
+            assert_ok!(request_unshield(who, 10, who as u8));
+        }
+        
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(UnshieldingRequests::<Test>::iter().count(), 2);
+        assert!(ShieldedPool::unshielding_cursor().is_some());
+        
+        // The second batch picks up the accounts after the cursor
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(UnshieldingRequests::<Test>::iter().count(), 0);
+        assert_eq!(ShieldedPool::total_shielded(), 400 - 40);
+        
+        // Finding nothing after the cursor wraps it around
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(ShieldedPool::unshielding_cursor(), None);
+    });
+}
+
+#[test]
+fn the_mock_verifier_requires_the_magic_prefix() {
+    ExtBuilder::default().build_and_execute(|| {
+        let shield_with = |seed, proof: Vec<u8>| {
+            ShieldedPool::shield(Origin::signed(1), 100, commitment(seed), ciphertext(seed), Proof(proof))
+        };
+        
+        assert_noop!(shield_with(1, vec![]), Error::<Test>::InvalidProof);
+        assert_noop!(shield_with(1, MockVerifier::MAGIC[1..].to_vec()), Error::<Test>::InvalidProof);
+        assert_noop!(shield_with(1, [b"x", MockVerifier::MAGIC].concat()), Error::<Test>::InvalidProof);
+        
+        // Anything may follow the prefix
+        assert_ok!(shield_with(1, [MockVerifier::MAGIC, b"payload"].concat()));
+    });
+}
+
+#[test]
+fn an_unshield_proof_over_another_amount_fails() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(shield(2, 100, 2));
+        run_to_block(2);
+        
+        // The proof attests to unshielding 100 to account 1
+        let root = ShieldedPool::merkle_root();
+        ProvenInputs::set(&Some(ShieldedPool::build_unshield_public_inputs(&root, &nullifier(1), &100, &1)));
+        
+        assert_noop!(unshield(1, 150, 1), Error::<Test>::InvalidProof);
+        assert_noop!(
+            ShieldedPool::request_unshield(Origin::signed(1), 100, 2, nullifier(1), root, valid_proof()),
+            Error::<Test>::InvalidProof
+        );
+        assert_ok!(unshield(1, 100, 1));
+    });
+}
+
+#[test]
+fn total_shielded_follows_shields_and_paid_unshields() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 300, 1));
+        assert_ok!(shield(2, 200, 2));
+        assert_eq!(ShieldedPool::total_shielded(), 500);
+        
+        // Queued requests leave the accumulator untouched until they are paid
+        assert_ok!(request_unshield(1, 120, 1));
+        assert_ok!(unshield(2, 80, 2));
+        assert_eq!(ShieldedPool::total_shielded(), 500);
+        
+        assert_ok!(ShieldedPool::process_unshielding_batch(Origin::root()));
+        assert_eq!(ShieldedPool::total_shielded(), 300);
+        assert_ok!(ShieldedPool::do_try_state());
+        
+        // Nothing can take out more than the accumulator holds
+        assert_noop!(unshield(3, 301, 3), Error::<Test>::InvalidUnshield);
+        assert_noop!(
+            ShieldedPool::write_down_pool(Origin::root(), 301),
+            Error::<Test>::ShieldedPoolUnderflow
+        );
+        assert_eq!(ShieldedPool::total_shielded(), 300);
+    });
+}
+
+#[test]
+fn note_ciphertexts_are_stored_by_commitment() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_eq!(ShieldedPool::encrypted_notes(commitment(1)), Some(ciphertext(1)));
+        
+        run_to_block(2);
+        let transfer = |ciphertexts| ShieldedPool::transfer_shielded(
+            Origin::signed(1),
+            vec![nullifier(1)],
+            vec![commitment(5), commitment(6)],
+            ciphertexts,
+            ShieldedPool::merkle_root(),
+            valid_proof(),
+        );
+        // Every output commitment needs its ciphertext
+        assert_noop!(transfer(vec![ciphertext(5)]), Error::<Test>::InvalidTransferSize);
+        assert_ok!(transfer(vec![ciphertext(5), ciphertext(6)]));
+        
+        assert_eq!(ShieldedPool::encrypted_notes(commitment(5)), Some(ciphertext(5)));
+        assert_eq!(ShieldedPool::encrypted_notes(commitment(6)), Some(ciphertext(6)));
+        assert_eq!(ShieldedPool::encrypted_notes(commitment(7)), None);
+    });
+}
+
+#[test]
+fn notes_in_range_returns_exactly_the_requested_blocks() {
+    ExtBuilder::default().build_and_execute(|| {
+        for seed in 1..=4u8 {
+            assert_ok!(shield(1, 100, seed));
+            run_to_block(System::block_number() + 1);
+        }
+        
+        assert_eq!(
+            ShieldedPool::notes_in_range(2, 3),
+            vec![(commitment(2), ciphertext(2).into_inner()), (commitment(3), ciphertext(3).into_inner())]
+        );
+        assert_eq!(ShieldedPool::notes_in_range(4, 4), vec![(commitment(4), ciphertext(4).into_inner())]);
+        assert!(ShieldedPool::notes_in_range(5, 9).is_empty());
+        
+        // A removed commitment drops out of its block
+        assert_ok!(ShieldedPool::force_remove_commitment(Origin::root(), commitment(2)));
+        assert_eq!(ShieldedPool::notes_in_range(2, 3), vec![(commitment(3), ciphertext(3).into_inner())]);
+    });
+}
+
+#[test]
+fn is_spent_reports_a_nullifier_only_once_published() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert!(!ShieldedPool::is_spent(&nullifier(1)));
+        
+        assert_ok!(request_unshield(1, 100, 1));
+        
+        assert!(ShieldedPool::is_spent(&nullifier(1)));
+        assert!(!ShieldedPool::is_spent(&nullifier(2)));
+    });
+}
+
+#[test]
+fn gateway_gating_rejects_normal_accounts_only_when_enabled() {
+    ExtBuilder::default().require_gateway(true).build_and_execute(|| {
+        assert_noop!(shield(1, 100, 1), Error::<Test>::NotGatewayAccount);
+        
+        assert_ok!(AtlasBalances::set_account_type(Origin::root(), 2, AccountType::Gateway));
+        assert_ok!(shield(2, 100, 2));
+        run_to_block(2);
+        assert_noop!(unshield(1, 100, 2), Error::<Test>::NotGatewayAccount);
+        assert_ok!(unshield(2, 100, 2));
+    });
+    
+    ExtBuilder::default().require_gateway(false).build_and_execute(|| {
+        assert_ok!(shield(1, 100, 1));
+        assert_ok!(request_unshield(1, 100, 1));
+    });
+}
+
+#[test]
+fn a_nullifier_cannot_be_requested_twice_in_one_block() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 200, 1));
+        run_to_block(2);
+        
+        assert_ok!(unshield(1, 100, 1));
+        assert_noop!(unshield(2, 100, 1), Error::<Test>::NullifierAlreadyExists);
+        
+        assert_eq!(ShieldedPool::unshielding_requests(1).len(), 1);
+        assert!(ShieldedPool::unshielding_requests(2).is_empty());
+    });
+}
+
+#[test]
+fn the_shield_fee_is_paid_into_the_fee_pot_on_top_of_the_amount() {
+    ExtBuilder::default().shield_fee(10).build_and_execute(|| {
+        // The amount, the fee and the existential deposit must all be covered
+        assert_noop!(
+            shield(1, INITIAL_BALANCE - 10, 1),
+            Error::<Test>::InsufficientPublicBalance
+        );
+        
+        assert_ok!(shield(1, 1_000, 1));
+        
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 1_000 - 10);
+        assert_eq!(Balances::free_balance(FEE_POT), 10);
+        assert_eq!(Balances::free_balance(ShieldedPool::account_id()), 1_000);
+        assert!(pool_events().contains(&PoolEvent::ShieldFeeCharged(1, 10)));
+        
+        assert_ok!(shield(2, 1_000, 2));
+        assert_eq!(Balances::free_balance(FEE_POT), 20);
+    });
+}
+
+#[test]
+fn cancelling_by_nullifier_frees_it_for_a_new_request() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(shield(1, 300, 1));
+        run_to_block(2);
+        assert_ok!(unshield(1, 100, 1));
+        assert_ok!(unshield(1, 100, 2));
+        
+        assert_noop!(
+            ShieldedPool::cancel_unshield_request(Origin::signed(1), nullifier(3)),
+            Error::<Test>::UnshieldRequestNotFound
+        );
+        // Only the owner of the request can cancel it
+        assert_noop!(
+            ShieldedPool::cancel_unshield_request(Origin::signed(2), nullifier(1)),
+            Error::<Test>::UnshieldRequestNotFound
+        );
+        
+        assert_ok!(ShieldedPool::cancel_unshield_request(Origin::signed(1), nullifier(1)));
+        assert!(!Nullifiers::<Test>::contains_key(nullifier(1)));
+        let pending = ShieldedPool::unshielding_requests(1);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].nullifier, nullifier(2));
+        assert!(pool_events().contains(&PoolEvent::UnshieldRequestCancelled(1, nullifier(1))));
+        
+        assert_ok!(unshield(1, 100, 1));
+        assert_eq!(ShieldedPool::unshielding_requests(1).len(), 2);
+    });
+}
+
+#[test]
+fn a_merkle_path_verifies_only_untampered() {
+    use sp_runtime::traits::{BlakeTwo256, Hash};
+    
+    ExtBuilder::default().build_and_execute(|| {
+        for seed in 1..=3 {
+            assert_ok!(shield(1, 100, seed));
+        }
+        run_to_block(2);
+        let root = ShieldedPool::merkle_root();
+        
+        // The sibling of every node on the way up from the second leaf
+        let mut path = Vec::new();
+        let mut empty_subtree = [0u8; 32];
+        let mut position = 1;
+        for level in 0..ShieldedPool::merkle_tree_depth() {
+            path.push(MerkleNodes::<Test>::get(level, position ^ 1).unwrap_or(empty_subtree));
+            empty_subtree = BlakeTwo256::hash(&[&empty_subtree[..], &empty_subtree[..]].concat()).0;
+            position /= 2;
+        }
+        let leaf = commitment(2).0;
+        
+        assert!(ShieldedPool::verify_merkle_path(leaf, &path, 1, root));
+        
+        assert!(!ShieldedPool::verify_merkle_path(commitment(9).0, &path, 1, root));
+        assert!(!ShieldedPool::verify_merkle_path(leaf, &path, 0, root));
+        assert!(!ShieldedPool::verify_merkle_path(leaf, &path[..path.len() - 1], 1, root));
+        assert!(!ShieldedPool::verify_merkle_path(leaf, &path, 1, [7; 32]));
+        let mut tampered = path.clone();
+        tampered[1][0] ^= 1;
+        assert!(!ShieldedPool::verify_merkle_path(leaf, &tampered, 1, root));
+    });
+}
+
+#[test]
+fn every_shield_emits_one_merkle_root_update_with_the_new_root() {
+    ExtBuilder::default().build_and_execute(|| {
+        for seed in 1..=3u8 {
+            assert_ok!(shield(1, 100, seed));
+            
+            let updates: Vec<_> = pool_events()
+                .into_iter()
+                .filter(|event| matches!(event, PoolEvent::MerkleRootUpdated(..)))
+                .collect();
+            assert_eq!(updates.len(), seed as usize);
+            assert_eq!(
+                updates.last(),
+                Some(&PoolEvent::MerkleRootUpdated(ShieldedPool::merkle_root(), seed as u32))
+            );
+        }
+    });
+}
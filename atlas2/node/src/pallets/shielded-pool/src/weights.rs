@@ -0,0 +1,48 @@
+//! Weights for the shielded pool pallet.
+//!
+//! These are placeholders until the benchmarks in `benchmarking.rs` have been run on reference
+//! hardware with the node's `benchmark pallet` subcommand, whose output replaces this file.
+//! The database component of each weight counts the reads and writes the extrinsic performs;
+//! the execution component is a deliberately generous estimate, not a measurement.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{constants::RocksDbWeight, Weight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for the shielded pool pallet.
+pub trait WeightInfo {
+    fn process_unshielding_batch(n: u32) -> Weight;
+}
+
+/// The estimated execution time of an extrinsic, before any per-item cost.
+const BASE: u64 = 50_000_000;
+
+/// The estimated execution time of each item an extrinsic iterates over.
+const PER_ITEM: u64 = 10_000_000;
+
+/// Weights for the shielded pool pallet using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn process_unshielding_batch(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(n as u64))
+            .saturating_add(T::DbWeight::get().reads(4))
+            .saturating_add(T::DbWeight::get().reads((4 as u64).saturating_mul(n as u64)))
+            .saturating_add(T::DbWeight::get().writes(3))
+            .saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn process_unshielding_batch(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(n as u64))
+            .saturating_add(RocksDbWeight::get().reads(4))
+            .saturating_add(RocksDbWeight::get().reads((4 as u64).saturating_mul(n as u64)))
+            .saturating_add(RocksDbWeight::get().writes(3))
+            .saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+    }
+}
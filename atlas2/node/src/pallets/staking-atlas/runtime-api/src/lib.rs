@@ -0,0 +1,42 @@
+//! # Staking Atlas Runtime API
+//!
+//! Runtime API definition used by wallets and block explorers to query the Aura-R DPoS
+//! staking state without reading raw storage.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_staking_atlas::{EraIndex, RewardDestination, StakingOverview, ValidatorStatus};
+use sp_runtime::Perbill;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+    /// Queries over the staking atlas pallet.
+    pub trait StakingAtlasApi<AccountId, Balance>
+    where
+        AccountId: Codec,
+        Balance: Codec,
+    {
+        /// The delegators backing `validator` and their current delegation amounts.
+        fn nominators_of(validator: AccountId) -> Vec<(AccountId, Balance)>;
+
+        /// The validator's self-stake as a fraction of its total stake, `None` for non-validators.
+        fn self_bond_ratio(who: AccountId) -> Option<Perbill>;
+
+        /// Where the rewards of `who` are paid, `Stash` when no payee has been set.
+        fn reward_destination(who: AccountId) -> RewardDestination<AccountId>;
+
+        /// The fraction of its expected blocks `who` authored over the last `eras` completed eras.
+        fn uptime(who: AccountId, eras: u32) -> Perbill;
+
+        /// The status of `who` as it should be displayed, resolving jail and stake sufficiency.
+        fn effective_status(who: AccountId) -> ValidatorStatus;
+
+        /// The reward `account` can still claim for `era` as a validator or delegator, `None`
+        /// if it has nothing to claim.
+        fn pending_reward(account: AccountId, era: EraIndex) -> Option<Balance>;
+
+        /// The validator stake, delegations and unbonding chunks of `account` in one query.
+        fn staking_overview(account: AccountId) -> StakingOverview<AccountId, Balance>;
+    }
+}
@@ -0,0 +1,256 @@
+//! Benchmarks for the staking atlas pallet.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+
+const SEED: u32 = 0;
+
+/// Give `who` enough free balance for any stake the benchmarks lock or reserve.
+fn fund<T: Config>(who: &T::AccountId) {
+    T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 2u32.into());
+}
+
+/// The self-stake every benchmark validator bonds, leaving room under `MaxStakeMultiplier`
+/// for delegations.
+fn validator_stake<T: Config>() -> BalanceOf<T> {
+    Pallet::<T>::effective_min_validator_stake()
+        .max(T::MinDelegationStake::get())
+        .saturating_mul(10u32.into())
+}
+
+/// Register a funded validator with no commission.
+fn create_validator<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+    let validator: T::AccountId = account(name, index, SEED);
+    fund::<T>(&validator);
+    Pallet::<T>::register_validator(
+        RawOrigin::Signed(validator.clone()).into(),
+        validator_stake::<T>(),
+        Perbill::zero(),
+    )
+    .expect("a funded validator can register");
+    validator
+}
+
+/// Delegate the minimum delegation from `who` to `validator`.
+fn create_delegation<T: Config>(who: &T::AccountId, validator: &T::AccountId) {
+    Pallet::<T>::delegate(
+        RawOrigin::Signed(who.clone()).into(),
+        T::Lookup::unlookup(validator.clone()),
+        T::MinDelegationStake::get(),
+    )
+    .expect("a funded delegator can delegate to an active validator");
+}
+
+/// Record a reward for `validator` in `era` to be paid out to it and `delegators`, each
+/// exposed with the minimum delegation.
+fn create_payout<T: Config>(validator: &T::AccountId, era: EraIndex, delegators: Vec<T::AccountId>) {
+    let own = validator_stake::<T>();
+    let mut delegations = BoundedVec::default();
+    let mut total = own;
+    for who in delegators {
+        let value = T::MinDelegationStake::get();
+        total = total.saturating_add(value);
+        delegations
+            .try_push(IndividualExposure { who, value })
+            .expect("bounded by MaxNominatorsPerValidator");
+    }
+    ErasStakers::<T>::insert(era, validator, Exposure { own, total, delegations });
+    ErasValidatorReward::<T>::insert(era, validator, EraValidatorReward {
+        amount: total,
+        commission: Perbill::zero(),
+    });
+}
+
+benchmarks! {
+    register_validator {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let stake = validator_stake::<T>();
+    }: _(RawOrigin::Signed(caller.clone()), stake, Perbill::zero())
+    verify {
+        assert!(Validators::<T>::contains_key(&caller));
+    }
+
+    delegate {
+        // The number of delegations the caller already has
+        let d in 0 .. T::MaxDelegationsPerDelegator::get().saturating_sub(1);
+
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        for i in 0 .. d {
+            let validator = create_validator::<T>("existing", i);
+            create_delegation::<T>(&caller, &validator);
+        }
+        let validator = create_validator::<T>("validator", 0);
+        let amount = T::MinDelegationStake::get();
+    }: _(RawOrigin::Signed(caller.clone()), T::Lookup::unlookup(validator.clone()), amount)
+    verify {
+        let delegator = Delegators::<T>::get(&caller).expect("the delegation was made");
+        assert!(delegator.delegations.contains_key(&validator));
+    }
+
+    delegate_batch {
+        // The number of targets
+        let n in 1 .. T::MaxDelegationsPerDelegator::get();
+        // The delegations the caller already has, capped to leave room for the batch
+        let d in 0 .. T::MaxDelegationsPerDelegator::get().saturating_sub(1);
+
+        let existing = d.min(T::MaxDelegationsPerDelegator::get().saturating_sub(n));
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        for i in 0 .. existing {
+            let validator = create_validator::<T>("existing", i);
+            create_delegation::<T>(&caller, &validator);
+        }
+        let mut targets = BoundedVec::default();
+        for i in 0 .. n {
+            let validator = create_validator::<T>("target", i);
+            targets
+                .try_push((T::Lookup::unlookup(validator), T::MinDelegationStake::get()))
+                .expect("bounded by MaxDelegationsPerDelegator");
+        }
+    }: _(RawOrigin::Signed(caller.clone()), targets)
+    verify {
+        let delegator = Delegators::<T>::get(&caller).expect("the delegations were made");
+        assert_eq!(delegator.delegations.len() as u32, existing + n);
+    }
+
+    undelegate {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let validator = create_validator::<T>("validator", 0);
+        create_delegation::<T>(&caller, &validator);
+
+        // Let the delegation age past `MinDelegationEras`
+        CurrentEra::<T>::put(T::MinDelegationEras::get().saturating_add(1));
+        let amount = T::MinDelegationStake::get();
+    }: _(RawOrigin::Signed(caller.clone()), T::Lookup::unlookup(validator), amount)
+    verify {
+        assert!(!Delegators::<T>::contains_key(&caller));
+        assert!(!UnbondingDelegations::<T>::get(&caller).is_empty());
+    }
+
+    increase_stake {
+        let caller = create_validator::<T>("validator", 0);
+        let amount = T::MinDelegationStake::get();
+        let before = Validators::<T>::get(&caller).expect("registered").self_stake;
+    }: _(RawOrigin::Signed(caller.clone()), amount)
+    verify {
+        let after = Validators::<T>::get(&caller).expect("registered").self_stake;
+        assert_eq!(after, before.saturating_add(amount));
+    }
+
+    decrease_stake {
+        let caller = create_validator::<T>("validator", 0);
+        let amount = validator_stake::<T>().saturating_sub(Pallet::<T>::effective_min_validator_stake());
+    }: _(RawOrigin::Signed(caller.clone()), amount)
+    verify {
+        assert!(!UnbondingStake::<T>::get(&caller).is_empty());
+    }
+
+    payout_stakers {
+        // The number of delegators paid
+        let n in 0 .. T::MaxNominatorsPerValidator::get();
+
+        let validator = create_validator::<T>("validator", 0);
+        let era: EraIndex = 0;
+        let delegators = (0 .. n).map(|i| {
+            let delegator: T::AccountId = account("delegator", i, SEED);
+            fund::<T>(&delegator);
+            delegator
+        }).collect();
+        create_payout::<T>(&validator, era, delegators);
+
+        let caller: T::AccountId = whitelisted_caller();
+    }: _(RawOrigin::Signed(caller), T::Lookup::unlookup(validator.clone()), era)
+    verify {
+        assert!(ClaimedRewards::<T>::get(era, &validator));
+    }
+
+    claim_all {
+        // The number of `(validator, era)` pairs visited, each with a payout pending
+        let n in 1 .. T::HistoryDepth::get();
+
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let validator = create_validator::<T>("validator", 0);
+        create_delegation::<T>(&caller, &validator);
+
+        // The delegation counts from era 1; every era up to `n` pays the caller and a full
+        // set of other delegators
+        let mut delegators = vec![caller.clone()];
+        for i in 1 .. T::MaxNominatorsPerValidator::get() {
+            let delegator: T::AccountId = account("delegator", i, SEED);
+            fund::<T>(&delegator);
+            delegators.push(delegator);
+        }
+        CurrentEra::<T>::put(n);
+        for era in 1 ..= n {
+            create_payout::<T>(&validator, era, delegators.clone());
+        }
+    }: _(RawOrigin::Signed(caller), n)
+    verify {
+        for era in 1 ..= n {
+            assert!(ClaimedRewards::<T>::get(era, &validator));
+        }
+    }
+
+    slash_validator {
+        // The number of delegators backing the validator
+        let n in 0 .. T::MaxNominatorsPerValidator::get();
+
+        let validator = create_validator::<T>("validator", 0);
+        for i in 0 .. n {
+            let delegator: T::AccountId = account("delegator", i, SEED);
+            fund::<T>(&delegator);
+            create_delegation::<T>(&delegator, &validator);
+        }
+    }: _(RawOrigin::Root, T::Lookup::unlookup(validator.clone()), Perbill::from_percent(10))
+    verify {
+        if T::SlashDeferDuration::get().is_zero() {
+            assert_eq!(ValidatorStatuses::<T>::get(&validator), ValidatorStatus::Slashed);
+        } else {
+            assert!(UnappliedSlashes::<T>::iter_values().any(|slashes| !slashes.is_empty()));
+        }
+    }
+
+    set_compound {
+        let caller: T::AccountId = whitelisted_caller();
+        fund::<T>(&caller);
+        let validator = create_validator::<T>("validator", 0);
+        create_delegation::<T>(&caller, &validator);
+    }: _(RawOrigin::Signed(caller.clone()), T::Lookup::unlookup(validator.clone()), true)
+    verify {
+        let delegator = Delegators::<T>::get(&caller).expect("the delegation was made");
+        assert!(delegator.delegations.get(&validator).expect("delegated").compound);
+    }
+
+    new_era {
+        // The number of registered validators, capped to the room left by the genesis validators
+        let c in 1 .. T::MaxCandidates::get();
+        // The number of delegators backing each validator
+        let n in 0 .. T::MaxNominatorsPerValidator::get();
+
+        let registered = c.min(T::MaxCandidates::get().saturating_sub(ValidatorCount::<T>::get()));
+        for i in 0 .. registered {
+            let validator = create_validator::<T>("validator", i);
+            for j in 0 .. n {
+                let delegator: T::AccountId = account("delegator", i * n + j, SEED);
+                fund::<T>(&delegator);
+                create_delegation::<T>(&delegator, &validator);
+            }
+        }
+        let era = Pallet::<T>::current_era();
+    }: {
+        Pallet::<T>::new_era(frame_system::Pallet::<T>::block_number());
+    }
+    verify {
+        assert_eq!(Pallet::<T>::current_era(), era + 1);
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::mock::ExtBuilder::default().build(), crate::mock::Test);
+}
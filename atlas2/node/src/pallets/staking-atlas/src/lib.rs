@@ -27,32 +27,80 @@ use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure,
-    traits::{Currency, Get, Imbalance, LockIdentifier, LockableCurrency, WithdrawReasons},
+    traits::{
+        Currency, EnsureOrigin, ExistenceRequirement, Get, Imbalance, LockIdentifier,
+        LockableCurrency, NamedReservableCurrency, OnUnbalanced, Randomness, ReservableCurrency,
+        WithdrawReasons,
+    },
     weights::{DispatchClass, Weight},
+    BoundedBTreeMap, BoundedVec, CloneNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound,
+    RuntimeDebugNoBound,
 };
 use frame_system::{ensure_signed, pallet_prelude::*};
+use pallet_atlas_balances::{AccountType, AccountTypeProvider};
 use scale_info::TypeInfo;
 use sp_runtime::{
-    traits::{AtLeast32BitUnsigned, CheckedSub, Convert, SaturatedConversion, StaticLookup, Zero},
+    traits::{
+        AtLeast32BitUnsigned, CheckedSub, Convert, Hash, SaturatedConversion, StaticLookup, Zero,
+    },
     Perbill, RuntimeDebug,
 };
 use sp_staking::SessionIndex;
 use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
+mod benchmarking;
+pub mod migrations;
+pub mod weights;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+pub use weights::WeightInfo;
+
+// Re-export pallet items so that they can be accessed from the crate namespace.
+pub use pallet::*;
+
+/// The identifier under which delegations and unbonding delegations are reserved, keeping
+/// them apart from reserves held by other pallets.
+pub const DELEGATION_RESERVE_ID: [u8; 8] = *b"atlsdelg";
+
 /// The staking atlas pallet's configuration trait.
 pub trait Config: frame_system::Config {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
 
-    /// The currency type used for staking.
-    type Currency: LockableCurrency<Self::AccountId>;
+    /// The currency type used for staking. Self-stake is locked, delegations are reserved
+    /// under `DELEGATION_RESERVE_ID`.
+    type Currency: LockableCurrency<Self::AccountId> +
+        NamedReservableCurrency<Self::AccountId, ReserveIdentifier = [u8; 8]>;
 
-    /// The period between eras.
+    /// The initial minimum number of blocks between eras; an era rotates at the first session
+    /// boundary after it has lasted `CurrentEraDuration` blocks, which starts out as this.
     type EraDuration: Get<Self::BlockNumber>;
 
+    /// The lowest era duration `set_era_duration` accepts.
+    type MinEraDuration: Get<Self::BlockNumber>;
+
+    /// The highest era duration `set_era_duration` accepts.
+    type MaxEraDuration: Get<Self::BlockNumber>;
+
     /// The number of validators to select per session.
     type ValidatorsCount: Get<u32>;
 
+    /// The maximum number of validators stored per era; selection is truncated to this bound.
+    type MaxValidatorsCount: Get<u32>;
+
+    /// The fewest validators a selection may yield before the previous era's set, less any
+    /// validator since reaped or slashed, is kept instead, so the chain never runs out of block
+    /// authors.
+    type MinActiveValidators: Get<u32>;
+
+    /// The maximum number of registered validators, bounding the candidates considered at
+    /// selection.
+    type MaxCandidates: Get<u32>;
+
     /// The minimum amount required to become a validator.
     type MinValidatorStake: Get<BalanceOf<Self>>;
 
@@ -62,14 +110,118 @@ pub trait Config: frame_system::Config {
     /// The maximum number of delegations per delegator.
     type MaxDelegationsPerDelegator: Get<u32>;
 
+    /// The maximum number of delegators (nominators) backing a single validator.
+    type MaxNominatorsPerValidator: Get<u32>;
+
+    /// How a new delegator is handled once a validator has `MaxNominatorsPerValidator`.
+    type OversubscriptionPolicy: Get<OversubscriptionPolicy>;
+
+    /// The number of eras a delegation must be held, counted from its `since_era`, before it
+    /// can be withdrawn. Zero disables the restriction.
+    type MinDelegationEras: Get<EraIndex>;
+
     /// The number of eras that rewards are paid after.
     type RewardPaymentDelay: Get<EraIndex>;
 
+    /// The number of eras an era's rewards stay claimable after the era; older unclaimed
+    /// rewards are swept at rotation.
+    type HistoryDepth: Get<EraIndex>;
+
+    /// The number of eras before an unclaimed era is swept that `RewardExpiringSoon` is emitted.
+    type RewardExpiryWarning: Get<EraIndex>;
+
+    /// The maximum number of expired reward entries removed at one era rotation; the rest of an
+    /// expired era is removed at the following rotations.
+    type MaxRewardsSweptPerEra: Get<u32>;
+
     /// The number of eras that locked staking funds must remain bonded for.
     type BondingDuration: Get<EraIndex>;
 
+    /// The maximum number of unbonding chunks an account may have pending at once.
+    type MaxUnlockingChunks: Get<u32>;
+
     /// The reputation weight in validator selection algorithm (0-100%).
     type ReputationWeight: Get<Perbill>;
+
+    /// The maximum reputation score; a score of 100 means a validator authored exactly its
+    /// expected share of blocks.
+    type MaxReputationScore: Get<BalanceOf<Self>>;
+
+    /// The share of its previous reputation score a validator keeps each era; the rest of the
+    /// new score comes from its performance in the era.
+    type ReputationDecay: Get<Perbill>;
+
+    /// The minimum share of its total stake a validator must keep as self-stake, both to be
+    /// selected and when decreasing its stake.
+    type MinSelfStakeRatio: Get<Perbill>;
+
+    /// The number of eras an active validator must wait between two stake decreases.
+    type StakeDecreaseCooldown: Get<EraIndex>;
+
+    /// The maximum multiple of its self-stake a validator's total stake may reach.
+    type MaxStakeMultiplier: Get<u32>;
+
+    /// The maximum commission a validator may charge.
+    type MaxCommission: Get<Perbill>;
+
+    /// The maximum length in bytes of a validator's display name.
+    type MaxValidatorNameLength: Get<u32>;
+
+    /// The maximum length in bytes of a validator's website.
+    type MaxValidatorWebsiteLength: Get<u32>;
+
+    /// The maximum length in bytes of a validator's contact details.
+    type MaxValidatorContactLength: Get<u32>;
+
+    /// The newly issued reward of each completed era, on top of the fees collected during it.
+    type EraReward: Get<BalanceOf<Self>>;
+
+    /// The maximum total issuance that reward minting may bring the currency up to.
+    type MaxTotalSupply: Get<BalanceOf<Self>>;
+
+    /// The number of eras a validator stays jailed after an offence.
+    type JailDuration: Get<EraIndex>;
+
+    /// The number of eras a reported slash waits before it is applied, during which it can be
+    /// cancelled. Zero applies slashes immediately.
+    type SlashDeferDuration: Get<EraIndex>;
+
+    /// The origin allowed to cancel deferred slashes (e.g. governance).
+    type SlashCancelOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Source of account types; only `Normal` accounts may become validators.
+    type AccountTypes: AccountTypeProvider<Self::AccountId>;
+
+    /// Source of randomness used to choose among validators tied at the selection boundary.
+    type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+
+    /// Handler notified of each newly selected validator set.
+    type OnNewValidatorSet: OnNewValidatorSet<Self::AccountId>;
+
+    /// Destination of slashed funds (e.g. treasury or reward pot).
+    type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+    /// Handler for the rounding dust left over from reward distribution (e.g. treasury or burn).
+    type DustRemoval: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+    /// Weight information for the extrinsics of this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+/// Notified of every new validator set selected at era rotation.
+///
+/// Lets runtimes pair this pallet with consensus engines other than Aura without going
+/// through the session machinery. `()` is a no-op and tuples notify each member in order.
+pub trait OnNewValidatorSet<AccountId> {
+    /// Called with the validators selected for `era`.
+    fn on_new_validator_set(era: EraIndex, validators: &[AccountId]);
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AccountId> OnNewValidatorSet<AccountId> for Tuple {
+    fn on_new_validator_set(era: EraIndex, validators: &[AccountId]) {
+        for_tuples!( #( Tuple::on_new_validator_set(era, validators); )* );
+    }
 }
 
 /// Alias for the balance type from the configuration.
@@ -80,9 +232,29 @@ pub type BalanceOf<T> =
 pub type NegativeImbalanceOf<T> =
     <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
 
+/// Alias for the validator details type bounded by the configuration.
+pub type ValidatorDetailsOf<T> = ValidatorDetails<
+    <T as Config>::MaxValidatorNameLength,
+    <T as Config>::MaxValidatorWebsiteLength,
+    <T as Config>::MaxValidatorContactLength,
+>;
+
+/// Alias for a deferred slash bounded by the configuration.
+pub type UnappliedSlashOf<T> = UnappliedSlash<
+    <T as frame_system::Config>::AccountId,
+    BalanceOf<T>,
+    <T as Config>::MaxNominatorsPerValidator,
+>;
+
 /// Era index type.
 pub type EraIndex = u32;
 
+/// Reward points earned by a validator within an era.
+pub type RewardPoint = u32;
+
+/// The reward points awarded to the author of a block.
+pub const BLOCK_AUTHORING_POINTS: RewardPoint = 20;
+
 /// A value placed in storage that represents a reputation score.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub struct ReputationScore<Balance> {
@@ -105,28 +277,208 @@ pub struct Validator<AccountId, Balance> {
     pub reputation: ReputationScore<Balance>,
     /// Whether the validator is currently active.
     pub is_active: bool,
+    /// Whether the validator is accepting new delegators.
+    pub delegations_open: bool,
+    /// The last era in which the validator was registered as active or selected.
+    pub last_active_era: EraIndex,
+    /// The share of its rewards the validator keeps before splitting the rest by stake.
+    pub commission: Perbill,
 }
 
-/// Delegator information.
+/// Preferences a validator applies to the delegations it accepts.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ValidatorPrefs<Balance> {
+    /// The minimum delegation the validator accepts, on top of the global `MinDelegationStake`.
+    pub min_delegation: Option<Balance>,
+}
+
+impl<Balance> Default for ValidatorPrefs<Balance> {
+    fn default() -> Self {
+        Self { min_delegation: None }
+    }
+}
+
+/// Self-declared details that help delegators tell validators apart.
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+#[scale_info(skip_type_params(NameLimit, WebsiteLimit, ContactLimit))]
+#[codec(mel_bound())]
+pub struct ValidatorDetails<NameLimit, WebsiteLimit, ContactLimit>
+where
+    NameLimit: Get<u32>,
+    WebsiteLimit: Get<u32>,
+    ContactLimit: Get<u32>,
+{
+    /// The validator's display name.
+    pub name: BoundedVec<u8, NameLimit>,
+    /// The validator's website.
+    pub website: BoundedVec<u8, WebsiteLimit>,
+    /// How to reach the validator's operator, e.g. an email address.
+    pub contact: BoundedVec<u8, ContactLimit>,
+}
+
+/// A reported slash waiting for its dispute window to pass.
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+#[scale_info(skip_type_params(MaxDelegators))]
+#[codec(mel_bound(AccountId: MaxEncodedLen, Balance: MaxEncodedLen))]
+pub struct UnappliedSlash<AccountId, Balance, MaxDelegators>
+where
+    AccountId: Clone + PartialEq + Eq + core::fmt::Debug,
+    Balance: Clone + PartialEq + Eq + core::fmt::Debug,
+    MaxDelegators: Get<u32>,
+{
+    /// The validator to slash.
+    pub validator: AccountId,
+    /// The share of the validator's self-stake and of each affected delegation to slash.
+    pub proportion: Perbill,
+    /// The validator's self-stake when the slash was reported.
+    pub own: Balance,
+    /// The delegators backing the validator when the slash was reported, with the amount each
+    /// delegated. Delegators who join later are not slashed.
+    pub delegators: BoundedVec<(AccountId, Balance), MaxDelegators>,
+}
+
+/// Everything about an account's staking position, as returned by `Pallet::staking_overview`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct StakingOverview<AccountId, Balance> {
+    /// Whether the account is a registered validator.
+    pub is_validator: bool,
+    /// The validator's self-stake, zero for non-validators.
+    pub self_stake: Balance,
+    /// The total stake backing the validator (self + delegated), zero for non-validators.
+    pub total_backing: Balance,
+    /// The validator's reputation score, zero for non-validators.
+    pub reputation: Balance,
+    /// The account's delegations, as `(validator, amount)`.
+    pub delegations: Vec<(AccountId, Balance)>,
+    /// Unbonding self-stake, as `(unlock_era, amount)` chunks.
+    pub unbonding_stake: Vec<(EraIndex, Balance)>,
+    /// Unbonding delegations, as `(unlock_era, amount)` chunks.
+    pub unbonding_delegations: Vec<(EraIndex, Balance)>,
+}
+
+/// A single delegation from a delegator to a validator.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
-pub struct Delegator<AccountId, Balance> {
+pub struct Delegation<Balance> {
+    /// The amount delegated.
+    pub amount: Balance,
+    /// The first era whose exposure snapshot includes this delegation.
+    pub since_era: EraIndex,
+    /// Whether rewards earned by this delegation are added to it rather than paid out.
+    pub compound: bool,
+    /// A top-up already included in `amount` that only counts from the given era onwards, as
+    /// `(effective_era, amount)`.
+    pub pending: Option<(EraIndex, Balance)>,
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> Delegation<Balance> {
+    /// Whether this delegation counts towards the exposure (and rewards) of `era`.
+    pub fn is_effective_in(&self, era: EraIndex) -> bool {
+        self.since_era <= era
+    }
+    
+    /// The part of `amount` that counts towards the exposure (and rewards) of `era`.
+    pub fn effective_amount(&self, era: EraIndex) -> Balance {
+        if !self.is_effective_in(era) {
+            return Zero::zero();
+        }
+        
+        match self.pending {
+            Some((effective_era, pending)) if effective_era > era => self.amount.saturating_sub(pending),
+            _ => self.amount,
+        }
+    }
+    
+    /// The part of `amount` that has been held for `min_eras` eras as of `era`, counting a
+    /// top-up from the era it became effective.
+    pub fn withdrawable_amount(&self, era: EraIndex, min_eras: EraIndex) -> Balance {
+        if era < self.since_era.saturating_add(min_eras) {
+            return Zero::zero();
+        }
+        
+        match self.pending {
+            Some((effective_era, pending)) if era < effective_era.saturating_add(min_eras) => {
+                self.amount.saturating_sub(pending)
+            },
+            _ => self.amount,
+        }
+    }
+    
+    /// Add `amount` to the delegation during `current_era`. Before the delegation itself is
+    /// effective the top-up simply joins it; afterwards it is pending until the next era.
+    pub fn top_up(&mut self, amount: Balance, current_era: EraIndex) {
+        self.amount = self.amount.saturating_add(amount);
+        
+        if !self.is_effective_in(current_era) {
+            return;
+        }
+        
+        let effective_era = current_era.saturating_add(1);
+        let pending = match self.pending {
+            // Another top-up of this era is still pending
+            Some((era, pending)) if era == effective_era => pending.saturating_add(amount),
+            // Any earlier top-up is already effective
+            _ => amount,
+        };
+        self.pending = Some((effective_era, pending));
+    }
+    
+    /// Remove `amount` from the delegation, leaving a pending top-up pending as long as the
+    /// rest of the delegation covers the removal.
+    pub fn reduce(&mut self, amount: Balance) {
+        self.amount = self.amount.saturating_sub(amount);
+        
+        if let Some((_, pending)) = self.pending.as_mut() {
+            *pending = (*pending).min(self.amount);
+        }
+        if self.pending.map_or(false, |(_, pending)| pending.is_zero()) {
+            self.pending = None;
+        }
+    }
+}
+
+/// Delegator information.
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, TypeInfo, MaxEncodedLen)]
+#[scale_info(skip_type_params(MaxDelegations))]
+#[codec(mel_bound(AccountId: MaxEncodedLen, Balance: MaxEncodedLen))]
+pub struct Delegator<AccountId, Balance, MaxDelegations>
+where
+    AccountId: Ord + Clone + PartialEq + Eq + core::fmt::Debug,
+    Balance: Clone + PartialEq + Eq + core::fmt::Debug,
+    MaxDelegations: Get<u32>,
+{
     /// The delegator's account.
     pub account: AccountId,
-    /// The delegations made by this delegator.
-    pub delegations: Vec<(AccountId, Balance)>,
-    /// The total staked amount.
+    /// The delegations made by this delegator, keyed by validator.
+    pub delegations: BoundedBTreeMap<AccountId, Delegation<Balance>, MaxDelegations>,
+    /// The total staked amount, cached sum of `delegations`.
     pub total_staked: Balance,
 }
 
 /// Exposure of a validator at a particular era.
-#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
-pub struct Exposure<AccountId, Balance> {
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, DefaultNoBound, TypeInfo, MaxEncodedLen)]
+#[scale_info(skip_type_params(MaxDelegators))]
+#[codec(mel_bound(AccountId: MaxEncodedLen, Balance: MaxEncodedLen))]
+pub struct Exposure<AccountId, Balance, MaxDelegators>
+where
+    AccountId: Clone + PartialEq + Eq + core::fmt::Debug,
+    Balance: Clone + PartialEq + Eq + core::fmt::Debug + Default,
+    MaxDelegators: Get<u32>,
+{
     /// The validator's own stake.
     pub own: Balance,
-    /// The total stake from delegators.
+    /// The total stake, own plus delegated.
     pub total: Balance,
     /// The delegations to this validator.
-    pub delegations: Vec<IndividualExposure<AccountId, Balance>>,
+    pub delegations: BoundedVec<IndividualExposure<AccountId, Balance>, MaxDelegators>,
+}
+
+/// A validator's reward for an era, fixed when the era's rewards are computed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct EraValidatorReward<Balance> {
+    /// The reward to split between the validator and its delegators.
+    pub amount: Balance,
+    /// The validator's commission at the time the reward was computed.
+    pub commission: Perbill,
 }
 
 /// A delegation from a delegator to a validator.
@@ -138,6 +490,50 @@ pub struct IndividualExposure<AccountId, Balance> {
     pub value: Balance,
 }
 
+/// How `delegate` handles a new delegator of a validator that already has the maximum number
+/// of delegators.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum OversubscriptionPolicy {
+    /// Reject the new delegation.
+    Reject,
+    /// Evict the smallest existing delegation if the new one is larger, rejecting it otherwise.
+    EvictSmallest,
+}
+
+/// Where the staking rewards of an account are paid.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum RewardDestination<AccountId> {
+    /// Pay into the staking account itself.
+    Stash,
+    /// Pay into the given account.
+    Account(AccountId),
+    /// Pay into the staking account and add the reward to the stake that earned it.
+    Compound,
+}
+
+impl<AccountId> Default for RewardDestination<AccountId> {
+    fn default() -> Self {
+        Self::Stash
+    }
+}
+
+/// Whether era rotation is forced or frozen, set by governance.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum Forcing {
+    /// Rotate eras once `CurrentEraDuration` has elapsed.
+    NotForcing,
+    /// Rotate at the next session boundary regardless of the era duration, then stop forcing.
+    ForceNew,
+    /// Never rotate until a new era is forced.
+    ForceNone,
+}
+
+impl Default for Forcing {
+    fn default() -> Self {
+        Self::NotForcing
+    }
+}
+
 /// The activity status of a validator.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub enum ValidatorStatus {
@@ -149,6 +545,14 @@ pub enum ValidatorStatus {
     Slashed,
     /// The validator has insufficient stake to be selected.
     InsufficientStake,
+    /// The account has declared its intent to validate but has not bonded any stake yet.
+    Declared,
+    /// The validator is jailed and excluded from selection until it unjails.
+    ///
+    /// Never stored; only reported by `Pallet::effective_status`.
+    Jailed,
+    /// The validator has paused validating and is skipped in selection until it reactivates.
+    Chilled,
 }
 
 #[frame_support::pallet]
@@ -157,8 +561,12 @@ pub mod pallet {
     use frame_support::pallet_prelude::*;
     use frame_system::pallet_prelude::*;
 
+    /// The current storage version.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
     #[pallet::pallet]
     #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::storage]
@@ -169,6 +577,17 @@ pub mod pallet {
     #[pallet::getter(fn active_era)]
     pub type ActiveEra<T> = StorageValue<_, EraIndex, ValueQuery>;
 
+    /// The session index at which each era started; eras only ever rotate on session boundaries.
+    #[pallet::storage]
+    #[pallet::getter(fn eras_start_session_index)]
+    pub type ErasStartSessionIndex<T> = StorageMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        SessionIndex,
+        OptionQuery,
+    >;
+
     #[pallet::storage]
     #[pallet::getter(fn era_start_block_number)]
     pub type EraStartBlockNumber<T: Config> = StorageMap<
@@ -195,10 +614,21 @@ pub mod pallet {
         _,
         Twox64Concat,
         T::AccountId,
-        Delegator<T::AccountId, BalanceOf<T>>,
+        Delegator<T::AccountId, BalanceOf<T>, T::MaxDelegationsPerDelegator>,
         OptionQuery,
     >;
 
+    /// The delegators backing each validator (reverse index of `Delegators`).
+    #[pallet::storage]
+    #[pallet::getter(fn validator_nominators)]
+    pub type ValidatorNominators<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        BoundedVec<T::AccountId, T::MaxNominatorsPerValidator>,
+        ValueQuery,
+    >;
+
     #[pallet::storage]
     #[pallet::getter(fn validator_count)]
     pub type ValidatorCount<T> = StorageValue<_, u32, ValueQuery>;
@@ -207,6 +637,29 @@ pub mod pallet {
     #[pallet::getter(fn minimum_validator_stake)]
     pub type MinimumValidatorStake<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+    #[pallet::type_value]
+    pub fn DefaultEraDuration<T: Config>() -> T::BlockNumber {
+        T::EraDuration::get()
+    }
+
+    /// The minimum number of blocks the current era lasts, `EraDuration` until set by
+    /// governance.
+    #[pallet::storage]
+    #[pallet::getter(fn current_era_duration)]
+    pub type CurrentEraDuration<T: Config> =
+        StorageValue<_, T::BlockNumber, ValueQuery, DefaultEraDuration<T>>;
+
+    /// The era duration set by governance, moved into `CurrentEraDuration` when the next era
+    /// starts.
+    #[pallet::storage]
+    #[pallet::getter(fn next_era_duration)]
+    pub type NextEraDuration<T: Config> = StorageValue<_, T::BlockNumber, OptionQuery>;
+
+    /// Whether the next era rotation is forced or frozen.
+    #[pallet::storage]
+    #[pallet::getter(fn force_era)]
+    pub type ForceEra<T: Config> = StorageValue<_, Forcing, ValueQuery>;
+
     #[pallet::storage]
     #[pallet::getter(fn eras_stakers)]
     pub type ErasStakers<T: Config> = StorageDoubleMap<
@@ -215,17 +668,21 @@ pub mod pallet {
         EraIndex,
         Twox64Concat,
         T::AccountId,
-        Exposure<T::AccountId, BalanceOf<T>>,
+        Exposure<T::AccountId, BalanceOf<T>, T::MaxNominatorsPerValidator>,
         ValueQuery,
     >;
 
+    /// The validators selected for each era.
+    ///
+    /// Bounded by `MaxValidatorsCount`. A `BoundedVec` encodes identically to the `Vec` it
+    /// replaces, so existing entries within the bound decode without a migration.
     #[pallet::storage]
     #[pallet::getter(fn eras_validator_list)]
     pub type ErasValidatorList<T: Config> = StorageMap<
         _,
         Twox64Concat,
         EraIndex,
-        Vec<T::AccountId>,
+        BoundedVec<T::AccountId, T::MaxValidatorsCount>,
         ValueQuery,
     >;
 
@@ -249,6 +706,42 @@ pub mod pallet {
         OptionQuery,
     >;
 
+    /// The reward of each validator of an era, awaiting `payout_stakers`.
+    #[pallet::storage]
+    #[pallet::getter(fn eras_validator_reward)]
+    pub type ErasValidatorReward<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        Twox64Concat,
+        T::AccountId,
+        EraValidatorReward<BalanceOf<T>>,
+        OptionQuery,
+    >;
+
+    /// The earliest era that may still have unclaimed rewards.
+    #[pallet::storage]
+    #[pallet::getter(fn earliest_unclaimed_era)]
+    pub type EarliestUnclaimedEra<T> = StorageValue<_, EraIndex, ValueQuery>;
+
+    /// The next era whose expired rewards are removed from storage.
+    #[pallet::storage]
+    #[pallet::getter(fn next_era_to_sweep)]
+    pub type NextEraToSweep<T> = StorageValue<_, EraIndex, ValueQuery>;
+
+    /// Whether the reward of a validator and its delegators has been paid out for an era.
+    #[pallet::storage]
+    #[pallet::getter(fn claimed_rewards)]
+    pub type ClaimedRewards<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        Twox64Concat,
+        T::AccountId,
+        bool,
+        ValueQuery,
+    >;
+
     #[pallet::storage]
     #[pallet::getter(fn validator_status)]
     pub type ValidatorStatuses<T: Config> = StorageMap<
@@ -260,748 +753,3058 @@ pub mod pallet {
         fn() -> ValidatorStatus { ValidatorStatus::Deregistered },
     >;
 
-    #[pallet::event]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-        /// A new era has been started. [era_index]
-        NewEra(EraIndex),
-        
-        /// A validator has been registered. [validator]
-        ValidatorRegistered(T::AccountId),
-        
-        /// A validator has been deregistered. [validator]
-        ValidatorDeregistered(T::AccountId),
-        
-        /// A new delegation has been created. [delegator, validator, amount]
-        DelegationCreated(T::AccountId, T::AccountId, BalanceOf<T>),
-        
-        /// A delegation has been withdrawn. [delegator, validator, amount]
-        DelegationWithdrawn(T::AccountId, T::AccountId, BalanceOf<T>),
-        
-        /// A validator's self-stake has been increased. [validator, amount]
-        ValidatorStakeIncreased(T::AccountId, BalanceOf<T>),
-        
-        /// A validator's self-stake has been decreased. [validator, amount]
-        ValidatorStakeDecreased(T::AccountId, BalanceOf<T>),
-        
-        /// A validator's reputation score has been updated. [validator, new_score]
-        ReputationUpdated(T::AccountId, BalanceOf<T>),
-        
-        /// Rewards have been paid out. [era_index, total_reward]
-        RewardsPaid(EraIndex, BalanceOf<T>),
-        
-        /// A validator has been slashed. [validator, amount]
-        ValidatorSlashed(T::AccountId, BalanceOf<T>),
-    }
+    /// Jailed validators and the era from which they may unjail.
+    #[pallet::storage]
+    #[pallet::getter(fn jailed)]
+    pub type Jailed<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        EraIndex,
+        OptionQuery,
+    >;
 
-    #[pallet::error]
-    pub enum Error<T> {
-        /// Not a validator.
-        NotValidator,
-        
-        /// Not a delegator.
-        NotDelegator,
-        
-        /// Already a validator.
-        AlreadyValidator,
-        
-        /// Already a delegator.
-        AlreadyDelegator,
-        
-        /// Validator is not active.
-        ValidatorNotActive,
-        
-        /// Insufficient stake to become a validator.
-        InsufficientStake,
-        
-        /// Insufficient stake to delegate.
-        InsufficientDelegationStake,
-        
-        /// Too many delegations for a single delegator.
-        TooManyDelegations,
-        
-        /// Cannot withdraw stake while active as a validator.
-        CannotWithdrawWhileActive,
-        
-        /// Cannot withdraw stake before the bonding period ends.
-        CannotWithdrawBeforeBondingPeriod,
-        
-        /// No rewards available for this era.
-        NoRewardsForEra,
-        
-        /// Rewards already claimed for this era.
-        RewardsAlreadyClaimed,
-    }
+    /// Where each staker's rewards are paid; unset means `RewardDestination::Stash`.
+    #[pallet::storage]
+    #[pallet::getter(fn payee)]
+    pub type Payee<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        RewardDestination<T::AccountId>,
+        OptionQuery,
+    >;
 
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-        /// Register as a validator.
-        ///
-        /// The dispatch origin must be Signed.
-        ///
-        /// # <weight>
-        /// - Independent of the arguments. Moderate complexity.
-        /// - O(1).
-        /// - Three DB entries.
-        /// # </weight>
-        #[pallet::weight(10_000)]
-        pub fn register_validator(
-            origin: OriginFor<T>,
-            #[pallet::compact] stake: BalanceOf<T>,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-            
-            // Check if already a validator
-            ensure!(!Validators::<T>::contains_key(&who), Error::<T>::AlreadyValidator);
-            
-            // Check minimum stake
-            let min_stake = T::MinValidatorStake::get();
-            ensure!(stake >= min_stake, Error::<T>::InsufficientStake);
-            
-            // Lock the stake
-            T::Currency::set_lock(
-                LockIdentifier(*b"stakeatls"),
-                &who,
-                stake,
-                WithdrawReasons::all(),
-            );
-            
-            // Create reputation score
-            let reputation = ReputationScore {
-                score: Zero::zero(),
+    /// The number of blocks each validator authored in each era.
+    #[pallet::storage]
+    #[pallet::getter(fn eras_authored_blocks)]
+    pub type ErasAuthoredBlocks<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        Twox64Concat,
+        T::AccountId,
+        u32,
+        ValueQuery,
+    >;
+
+    /// The total number of blocks authored in each era.
+    #[pallet::storage]
+    #[pallet::getter(fn eras_block_count)]
+    pub type ErasBlockCount<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        u32,
+        ValueQuery,
+    >;
+
+    /// The reward points each validator earned in each era, awarded by `reward_by_ids`.
+    #[pallet::storage]
+    #[pallet::getter(fn eras_reward_points)]
+    pub type ErasRewardPoints<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        Twox64Concat,
+        T::AccountId,
+        RewardPoint,
+        ValueQuery,
+    >;
+
+    /// The total reward points earned by all validators in each era.
+    #[pallet::storage]
+    #[pallet::getter(fn eras_total_reward_points)]
+    pub type ErasTotalRewardPoints<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        RewardPoint,
+        ValueQuery,
+    >;
+
+    /// The era in which each active validator last decreased its self-stake.
+    #[pallet::storage]
+    #[pallet::getter(fn last_stake_decrease)]
+    pub type LastStakeDecrease<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        EraIndex,
+        OptionQuery,
+    >;
+
+    /// The delegation preferences of each validator.
+    #[pallet::storage]
+    #[pallet::getter(fn validator_prefs)]
+    pub type ValidatorPreferences<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        ValidatorPrefs<BalanceOf<T>>,
+        ValueQuery,
+    >;
+
+    /// The self-declared details of each validator.
+    #[pallet::storage]
+    #[pallet::getter(fn validator_metadata)]
+    pub type ValidatorMetadata<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        ValidatorDetailsOf<T>,
+        OptionQuery,
+    >;
+
+    /// Reported slashes, keyed by the era at whose start they are applied.
+    #[pallet::storage]
+    #[pallet::getter(fn unapplied_slashes)]
+    pub type UnappliedSlashes<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        BoundedVec<UnappliedSlashOf<T>, T::MaxCandidates>,
+        ValueQuery,
+    >;
+
+    /// Self-stake that is unbonding, as `(unlock_era, amount)` chunks in the order they were
+    /// unbonded. The amounts remain locked until withdrawn with `withdraw_unbonded`.
+    #[pallet::storage]
+    #[pallet::getter(fn unbonding_stake)]
+    pub type UnbondingStake<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        BoundedVec<(EraIndex, BalanceOf<T>), T::MaxUnlockingChunks>,
+        ValueQuery,
+    >;
+
+    /// Undelegated stake that is unbonding, as `(unlock_era, amount)` chunks in the order it
+    /// was undelegated. The amounts remain reserved until withdrawn with `withdraw_unbonded`.
+    #[pallet::storage]
+    #[pallet::getter(fn unbonding_delegations)]
+    pub type UnbondingDelegations<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        BoundedVec<(EraIndex, BalanceOf<T>), T::MaxUnlockingChunks>,
+        ValueQuery,
+    >;
+
+    /// Transaction fees collected in the current era, added to its reward at rotation.
+    #[pallet::storage]
+    #[pallet::getter(fn reward_fee_pot)]
+    pub type RewardFeePot<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    /// Reward payouts that failed to be deposited and are owed to the account.
+    #[pallet::storage]
+    #[pallet::getter(fn failed_payouts)]
+    pub type FailedPayouts<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        EraIndex,
+        Twox64Concat,
+        T::AccountId,
+        BalanceOf<T>,
+        ValueQuery,
+    >;
+
+    /// Genesis configuration of the staking atlas pallet.
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// The validators registered at genesis with their self-stake; they form the validator
+        /// set of era 0.
+        pub initial_validators: Vec<(T::AccountId, BalanceOf<T>)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self { initial_validators: Vec::new() }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            for (who, stake) in self.initial_validators.iter() {
+                assert!(
+                    !Validators::<T>::contains_key(who),
+                    "Initial validators must not contain duplicates"
+                );
+                assert!(
+                    *stake >= Pallet::<T>::effective_min_validator_stake(),
+                    "Initial validator stake is below the minimum validator stake"
+                );
+                assert!(
+                    T::Currency::free_balance(who) >= *stake,
+                    "Initial validator does not have enough balance to stake"
+                );
+                
+                T::Currency::set_lock(
+                    LockIdentifier(*b"stakeatls"),
+                    who,
+                    *stake,
+                    WithdrawReasons::all(),
+                );
+                
+                Validators::<T>::insert(who, Validator {
+                    account: who.clone(),
+                    self_stake: *stake,
+                    total_stake: *stake,
+                    reputation: ReputationScore { score: Zero::zero(), last_updated: 0 },
+                    is_active: true,
+                    delegations_open: true,
+                    last_active_era: 0,
+                    commission: Perbill::zero(),
+                });
+                ValidatorStatuses::<T>::insert(who, ValidatorStatus::Active);
+                ValidatorCount::<T>::mutate(|count| *count = count.saturating_add(1));
+            }
+            
+            // Seed the validator set of era 0, handed to pallet-session at genesis
+            let _ = Pallet::<T>::select_validators();
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A new era has been started. [era_index]
+        NewEra(EraIndex),
+        
+        /// A validator has been registered with the given self-stake in the given era.
+        ValidatorRegistered {
+            who: T::AccountId,
+            self_stake: BalanceOf<T>,
+            era: EraIndex,
+        },
+        
+        /// A validator has been deregistered. [validator]
+        ValidatorDeregistered(T::AccountId),
+        
+        /// A new delegation has been created. [delegator, validator, amount]
+        DelegationCreated(T::AccountId, T::AccountId, BalanceOf<T>),
+        
+        /// A delegation has been withdrawn. [delegator, validator, amount]
+        DelegationWithdrawn(T::AccountId, T::AccountId, BalanceOf<T>),
+        
+        /// A validator's self-stake has been increased. [validator, amount]
+        ValidatorStakeIncreased(T::AccountId, BalanceOf<T>),
+        
+        /// A validator's self-stake has been decreased. [validator, amount]
+        ValidatorStakeDecreased(T::AccountId, BalanceOf<T>),
+        
+        /// A validator's reputation score has been updated. [validator, new_score]
+        ReputationUpdated(T::AccountId, BalanceOf<T>),
+        
+        /// A validator has been slashed. [validator, amount]
+        ValidatorSlashed(T::AccountId, BalanceOf<T>),
+        
+        /// A validator reached its nominator cap and stopped accepting delegators. [validator]
+        ValidatorFull(T::AccountId),
+        
+        /// Era state has been resynchronised after a revert. [era_index]
+        EraResynced(EraIndex),
+        
+        /// Minting was throttled by the total supply cap. [withheld_amount]
+        SupplyCapReached(BalanceOf<T>),
+        
+        /// A stale validator has been removed from storage. [validator]
+        ValidatorReaped(T::AccountId),
+        
+        /// A validator has been jailed. [validator, release_era]
+        ValidatorJailed(T::AccountId, EraIndex),
+        
+        /// A validator has been released from jail. [validator]
+        ValidatorUnjailed(T::AccountId),
+        
+        /// A reward payout failed and was recorded for retry. [who, era_index, amount]
+        PayoutFailed(T::AccountId, EraIndex, BalanceOf<T>),
+        
+        /// A previously failed reward payout has been paid, fully or up to the supply cap.
+        /// [who, era_index, amount_minted]
+        PayoutRetried(T::AccountId, EraIndex, BalanceOf<T>),
+        
+        /// A staker's reward destination has been set. [who, destination]
+        PayeeSet(T::AccountId, RewardDestination<T::AccountId>),
+        
+        /// The minimum validator stake has been set. [amount]
+        MinimumValidatorStakeSet(BalanceOf<T>),
+        
+        /// The era duration has been set, to apply from the next era. [blocks]
+        EraDurationSet(T::BlockNumber),
+        
+        /// Too few validators could be selected, so the previous era's set was kept. [era_index]
+        ValidatorSetRetained(EraIndex),
+        
+        /// An account has declared its intent to become a validator. [who]
+        IntentDeclared(T::AccountId),
+        
+        /// Collected transaction fees have been added to an era's reward. [era_index, amount]
+        FeesAddedToReward(EraIndex, BalanceOf<T>),
+        
+        /// Self-stake has started unbonding. [validator, amount, unlock_era]
+        Unbonded(T::AccountId, BalanceOf<T>, EraIndex),
+        
+        /// Unbonded self-stake has been withdrawn and unlocked. [who, amount]
+        Withdrawn(T::AccountId, BalanceOf<T>),
+        
+        /// A validator's commission has been set. [validator, commission]
+        CommissionSet(T::AccountId, Perbill),
+        
+        /// A validator's preferences have been set. [validator, prefs]
+        ValidatorPrefsSet(T::AccountId, ValidatorPrefs<BalanceOf<T>>),
+        
+        /// A validator's metadata has been set. [validator]
+        ValidatorMetadataUpdated(T::AccountId),
+        
+        /// The rewards of a validator and its delegators have been paid out. [validator, era_index, total_paid]
+        StakersPaidOut(T::AccountId, EraIndex, BalanceOf<T>),
+        
+        /// A delegator claimed the pending payouts of its validators. [delegator, payouts_claimed]
+        AllClaimed(T::AccountId, u32),
+        
+        /// The unclaimed rewards of an era will be swept within `RewardExpiryWarning` eras. [era_index]
+        RewardExpiringSoon(EraIndex),
+        
+        /// A delegator's total stake has been corrected to the sum of its delegations. [delegator, old_total, new_total]
+        DelegatorReconciled(T::AccountId, BalanceOf<T>, BalanceOf<T>),
+        
+        /// A validator has paused validating. [validator]
+        ValidatorChilled(T::AccountId),
+        
+        /// A chilled validator has resumed validating. [validator]
+        ValidatorReactivated(T::AccountId),
+        
+        /// The smallest delegation of an oversubscribed validator was evicted to make room for a larger one. [delegator, validator, amount]
+        DelegationEvicted(T::AccountId, T::AccountId, BalanceOf<T>),
+        
+        /// Unbonding self-stake has been returned to the bonded self-stake. [validator, amount]
+        Rebonded(T::AccountId, BalanceOf<T>),
+        
+        /// A slash has been reported and will be applied at the start of an era. [validator, proportion, apply_era]
+        SlashDeferred(T::AccountId, Perbill, EraIndex),
+        
+        /// A deferred slash has been cancelled. [validator, apply_era]
+        DeferredSlashCancelled(T::AccountId, EraIndex),
+        
+        /// Era rotation has been forced or frozen. [mode]
+        ForceEraSet(Forcing),
+        
+        /// A delegator has turned reward compounding on or off for a delegation.
+        /// [delegator, validator, compound]
+        DelegationCompoundSet(T::AccountId, T::AccountId, bool),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Not a validator.
+        NotValidator,
+        
+        /// Not a delegator.
+        NotDelegator,
+        
+        /// Already a validator.
+        AlreadyValidator,
+        
+        /// Already a delegator.
+        AlreadyDelegator,
+        
+        /// Validator is not active.
+        ValidatorNotActive,
+        
+        /// Insufficient stake to become a validator.
+        InsufficientStake,
+        
+        /// Insufficient stake to delegate.
+        InsufficientDelegationStake,
+        
+        /// Too many delegations for a single delegator.
+        TooManyDelegations,
+        
+        /// Cannot withdraw stake while active as a validator.
+        CannotWithdrawWhileActive,
+        
+        /// Cannot withdraw stake before the bonding period ends.
+        CannotWithdrawBeforeBondingPeriod,
+        
+        /// No rewards available for this era.
+        NoRewardsForEra,
+        
+        /// Rewards already claimed for this era.
+        RewardsAlreadyClaimed,
+        
+        /// The validator is not accepting new delegators.
+        DelegationsClosed,
+        
+        /// The validator is not stale and cannot be reaped yet.
+        ValidatorNotStale,
+        
+        /// The validator is not jailed.
+        NotJailed,
+        
+        /// The validator's jail period has not ended yet.
+        StillJailed,
+        
+        /// Depositing a reward into the account failed.
+        DepositFailed,
+        
+        /// There is no failed payout recorded for this account and era.
+        NoFailedPayout,
+        
+        /// The account is neither a validator nor a delegator.
+        NotStaker,
+        
+        /// The account has already declared its intent to validate.
+        AlreadyDeclared,
+        
+        /// The validator has only declared its intent and has no bonded stake.
+        NotBonded,
+        
+        /// The delegation has not been held for `MinDelegationEras` yet.
+        DelegationTooYoung,
+        
+        /// Too many unbonding chunks are pending; withdraw matured ones first.
+        NoMoreChunks,
+        
+        /// The commission exceeds `MaxCommission`.
+        CommissionTooHigh,
+        
+        /// The decrease would take the validator's self-stake below `MinSelfStakeRatio`.
+        SelfStakeRatioTooLow,
+        
+        /// The validator decreased its stake too recently.
+        StakeDecreaseInCooldown,
+        
+        /// Only `Normal` accounts may become validators.
+        InvalidAccountType,
+        
+        /// The validator's total stake would exceed `MaxStakeMultiplier` times its self-stake.
+        SelfBondTooLow,
+        
+        /// The maximum number of validator candidates has been reached.
+        TooManyCandidates,
+        
+        /// The validator is not chilled.
+        NotChilled,
+        
+        /// The validator has the maximum number of delegators and the delegation cannot replace
+        /// any of them.
+        TooManyDelegators,
+        
+        /// There is no unbonding stake to rebond.
+        NoUnlockChunk,
+        
+        /// The account's free balance cannot cover the delegations.
+        InsufficientBalance,
+        
+        /// Too many slashes are already deferred to the same era.
+        TooManyUnappliedSlashes,
+        
+        /// The era duration is outside `MinEraDuration..=MaxEraDuration`.
+        InvalidEraDuration,
+        
+        /// There is no deferred slash of the validator in that era.
+        NoUnappliedSlash,
+        
+        /// The account is affected by a deferred slash that has not been applied yet.
+        SlashPending,
+        
+        /// The validator still has delegations and cannot withdraw all of its self-stake.
+        ValidatorHasDelegations,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Declare the intent to become a validator without bonding any stake.
+        ///
+        /// Reserves the validator identity so that the operator can set up session keys before
+        /// bonding. The account becomes `Declared` and must later call `register_validator` to
+        /// bond its stake and become `Active`.
+        ///
+        /// The dispatch origin must be Signed.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn declare_intent(
+            origin: OriginFor<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            ensure!(!Validators::<T>::contains_key(&who), Error::<T>::AlreadyValidator);
+            ensure!(!Self::is_declared(&who), Error::<T>::AlreadyDeclared);
+            
+            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Declared);
+            
+            Self::deposit_event(Event::IntentDeclared(who));
+            
+            Ok(())
+        }
+        
+        /// Register as a validator.
+        ///
+        /// The dispatch origin must be Signed. A `Declared` account transitions to `Active`.
+        /// At most `MaxCandidates` validators may be registered at once.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Three DB entries.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::register_validator())]
+        pub fn register_validator(
+            origin: OriginFor<T>,
+            #[pallet::compact] stake: BalanceOf<T>,
+            commission: Perbill,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            // Check if already a validator
+            ensure!(!Validators::<T>::contains_key(&who), Error::<T>::AlreadyValidator);
+            
+            // Contract and gateway accounts cannot validate
+            ensure!(
+                T::AccountTypes::account_type(&who) == AccountType::Normal,
+                Error::<T>::InvalidAccountType
+            );
+            ensure!(commission <= T::MaxCommission::get(), Error::<T>::CommissionTooHigh);
+            ensure!(
+                ValidatorCount::<T>::get() < T::MaxCandidates::get(),
+                Error::<T>::TooManyCandidates
+            );
+            
+            // Check minimum stake
+            let min_stake = Self::effective_min_validator_stake();
+            ensure!(stake >= min_stake, Error::<T>::InsufficientStake);
+            
+            // Create reputation score
+            let reputation = ReputationScore {
+                score: Zero::zero(),
                 last_updated: Self::current_era(),
             };
             
-            // Create validator
-            let validator = Validator {
-                account: who.clone(),
-                self_stake: stake,
-                total_stake: stake,
-                reputation,
-                is_active: true,
+            // Create validator
+            let validator = Validator {
+                account: who.clone(),
+                self_stake: stake,
+                total_stake: stake,
+                reputation,
+                is_active: true,
+                delegations_open: true,
+                last_active_era: Self::current_era(),
+                commission,
+            };
+            
+            // Store validator
+            Validators::<T>::insert(&who, validator);
+            
+            // Lock the stake, keeping any chunks still unbonding from an earlier registration
+            // locked as well
+            Self::update_stake_lock(&who);
+            
+            // Update validator status
+            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Active);
+            
+            // Update validator count
+            let count = ValidatorCount::<T>::get().saturating_add(1);
+            ValidatorCount::<T>::put(count);
+            
+            Self::deposit_event(Event::ValidatorRegistered {
+                who,
+                self_stake: stake,
+                era: Self::current_era(),
+            });
+            
+            Ok(())
+        }
+        
+        /// Deregister as a validator.
+        ///
+        /// The dispatch origin must be Signed and the account must be currently registered as a validator.
+        /// The validator's metadata is removed if `clear_metadata` is set. A validator with a
+        /// deferred slash pending cannot deregister until the slash is applied or cancelled.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Three DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn deregister_validator(
+            origin: OriginFor<T>,
+            clear_metadata: bool,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            // Check if is a validator with bonded stake
+            ensure!(!Self::is_declared(&who), Error::<T>::NotBonded);
+            ensure!(Validators::<T>::contains_key(&who), Error::<T>::NotValidator);
+            
+            // Check if validator is active and can be deregistered
+            let validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            ensure!(!Self::has_pending_slash(&who), Error::<T>::SlashPending);
+            
+            // Update validator status
+            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Deregistered);
+            
+            // Update validator data
+            let updated_validator = Validator {
+                is_active: false,
+                last_active_era: Self::current_era(),
+                ..validator
+            };
+            Validators::<T>::insert(&who, updated_validator);
+            
+            // Update validator count
+            let count = ValidatorCount::<T>::get().saturating_sub(1);
+            ValidatorCount::<T>::put(count);
+            
+            if clear_metadata {
+                ValidatorMetadata::<T>::remove(&who);
+            }
+            
+            // Note: We don't remove the lock on the stake here.
+            // The stake will be unlocked after the bonding period.
+            
+            Self::deposit_event(Event::ValidatorDeregistered(who));
+            
+            Ok(())
+        }
+        
+        /// Pause validating without deregistering.
+        ///
+        /// The dispatch origin must be Signed and the account must be an active validator. A
+        /// chilled validator is skipped in selection and accepts no new delegations, but keeps
+        /// its stake, reputation and delegations until it calls `reactivate`.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Two DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn chill(
+            origin: OriginFor<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            ensure!(validator.is_active, Error::<T>::ValidatorNotActive);
+            
+            validator.is_active = false;
+            Validators::<T>::insert(&who, validator);
+            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Chilled);
+            
+            Self::deposit_event(Event::ValidatorChilled(who));
+            
+            Ok(())
+        }
+        
+        /// Resume validating after `chill`.
+        ///
+        /// The dispatch origin must be Signed and the account must be a chilled validator. It is
+        /// considered for selection again from the next era rotation.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Two DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn reactivate(
+            origin: OriginFor<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            ensure!(
+                ValidatorStatuses::<T>::get(&who) == ValidatorStatus::Chilled,
+                Error::<T>::NotChilled
+            );
+            
+            validator.is_active = true;
+            Validators::<T>::insert(&who, validator);
+            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Active);
+            
+            Self::deposit_event(Event::ValidatorReactivated(who));
+            
+            Ok(())
+        }
+        
+        /// Delegate tokens to a validator.
+        ///
+        /// The dispatch origin must be Signed and the validator's status must be `Active`. The
+        /// validator's total stake may not exceed `MaxStakeMultiplier` times its self-stake. A
+        /// new delegator of a validator that already has `MaxNominatorsPerValidator` delegators
+        /// is handled by `OversubscriptionPolicy`.
+        ///
+        /// A new delegation, and a top-up of an existing one, only counts towards the
+        /// validator's exposure from the next era.
+        ///
+        /// # <weight>
+        /// - O(D) where D is the caller's number of delegations, bounded by
+        ///   `MaxDelegationsPerDelegator` and charged for the bound.
+        /// - Three DB entries.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::delegate(T::MaxDelegationsPerDelegator::get()))]
+        pub fn delegate(
+            origin: OriginFor<T>,
+            validator: <T::Lookup as StaticLookup>::Source,
+            #[pallet::compact] amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let validator = T::Lookup::lookup(validator)?;
+            
+            Self::do_delegate(&who, validator, amount)
+        }
+        
+        /// Delegate to several validators at once.
+        ///
+        /// The dispatch origin must be Signed. Every target is checked as in `delegate`, and the
+        /// caller's free balance must cover the whole batch. If any target fails, no delegation
+        /// of the batch is made.
+        ///
+        /// # <weight>
+        /// - O(N) where N is the number of targets, bounded by `MaxDelegationsPerDelegator`.
+        /// - Three DB entries per target, each charged like a `delegate` at the
+        ///   `MaxDelegationsPerDelegator` bound.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::delegate_batch(
+            targets.len() as u32,
+            T::MaxDelegationsPerDelegator::get(),
+        ))]
+        pub fn delegate_batch(
+            origin: OriginFor<T>,
+            targets: BoundedVec<(<T::Lookup as StaticLookup>::Source, BalanceOf<T>), T::MaxDelegationsPerDelegator>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            let batch_total = targets
+                .iter()
+                .fold(Zero::zero(), |total: BalanceOf<T>, (_, amount)| total.saturating_add(*amount));
+            ensure!(
+                T::Currency::free_balance(&who) >= batch_total,
+                Error::<T>::InsufficientBalance
+            );
+            
+            for (validator, amount) in targets {
+                let validator = T::Lookup::lookup(validator)?;
+                Self::do_delegate(&who, validator, amount)?;
+            }
+            
+            Ok(())
+        }
+        
+        /// Undelegate tokens from a validator.
+        ///
+        /// The dispatch origin must be Signed and the account must have delegated to the validator.
+        /// The delegation must have been held for at least `MinDelegationEras` since it became
+        /// effective; a top-up counts from the era it became effective. The amount stays reserved in `UnbondingDelegations` for `BondingDuration`
+        /// eras and is then unreserved with `withdraw_unbonded`.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Four DB entries.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::undelegate())]
+        pub fn undelegate(
+            origin: OriginFor<T>,
+            validator: <T::Lookup as StaticLookup>::Source,
+            #[pallet::compact] amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let validator = T::Lookup::lookup(validator)?;
+            
+            // Check if is a delegator
+            ensure!(Delegators::<T>::contains_key(&who), Error::<T>::NotDelegator);
+            let mut delegator = Delegators::<T>::get(&who).ok_or(Error::<T>::NotDelegator)?;
+            
+            // Find the delegation
+            let delegation = delegator.delegations.get(&validator)
+                .ok_or(Error::<T>::NotDelegator)?;
+            let current_delegation = delegation.amount;
+            
+            // Discourage reward hopping: the delegation, and any top-up of it, must have been
+            // held long enough
+            ensure!(amount <= current_delegation, Error::<T>::InsufficientDelegationStake);
+            ensure!(
+                amount <= delegation.withdrawable_amount(Self::current_era(), T::MinDelegationEras::get()),
+                Error::<T>::DelegationTooYoung
+            );
+            
+            // Check if validator exists
+            ensure!(Validators::<T>::contains_key(&validator), Error::<T>::NotValidator);
+            let mut validator_data = Validators::<T>::get(&validator).ok_or(Error::<T>::NotValidator)?;
+            
+            // Queue the amount for unbonding; it stays reserved until it is withdrawn
+            let unlock_era = Self::current_era().saturating_add(T::BondingDuration::get());
+            UnbondingDelegations::<T>::try_mutate(&who, |chunks| chunks.try_push((unlock_era, amount)))
+                .map_err(|_| Error::<T>::NoMoreChunks)?;
+            
+            // Update validator's total stake
+            validator_data.total_stake = validator_data.total_stake.saturating_sub(amount);
+            
+            // Update delegator data
+            if amount == current_delegation {
+                // Remove delegation completely
+                delegator.delegations.remove(&validator);
+                
+                // Free the nominator slot and reopen the validator to new delegators
+                ValidatorNominators::<T>::mutate(&validator, |nominators| {
+                    nominators.retain(|n| *n != who);
+                });
+                validator_data.delegations_open = true;
+            } else {
+                // Reduce delegation amount
+                if let Some(delegation) = delegator.delegations.get_mut(&validator) {
+                    delegation.reduce(amount);
+                }
+            }
+            
+            Validators::<T>::insert(&validator, validator_data);
+            
+            delegator.total_staked = delegator.total_staked.saturating_sub(amount);
+            
+            if delegator.delegations.is_empty() {
+                // Remove delegator if no delegations left
+                Delegators::<T>::remove(&who);
+            } else {
+                // Update delegator
+                Delegators::<T>::insert(&who, delegator);
+            }
+            
+            Self::deposit_event(Event::DelegationWithdrawn(who, validator, amount));
+            
+            Ok(())
+        }
+        
+        /// Remove a delegator's whole delegation to the caller.
+        ///
+        /// The dispatch origin must be Signed and the account must be a registered validator.
+        /// The kicked amount unbonds like an undelegation and the delegator keeps its other
+        /// delegations. `MinDelegationEras` does not apply.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Five DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn kick_delegator(
+            origin: OriginFor<T>,
+            delegator: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            let validator = ensure_signed(origin)?;
+            let who = T::Lookup::lookup(delegator)?;
+            
+            let mut validator_data = Validators::<T>::get(&validator).ok_or(Error::<T>::NotValidator)?;
+            let mut delegator = Delegators::<T>::get(&who).ok_or(Error::<T>::NotDelegator)?;
+            let amount = delegator.delegations.remove(&validator)
+                .ok_or(Error::<T>::NotDelegator)?
+                .amount;
+            
+            // Queue the kicked amount for unbonding; it stays reserved until it is withdrawn
+            let unlock_era = Self::current_era().saturating_add(T::BondingDuration::get());
+            UnbondingDelegations::<T>::try_mutate(&who, |chunks| chunks.try_push((unlock_era, amount)))
+                .map_err(|_| Error::<T>::NoMoreChunks)?;
+            
+            // Free the nominator slot and reopen the validator to new delegators
+            ValidatorNominators::<T>::mutate(&validator, |nominators| {
+                nominators.retain(|n| *n != who);
+            });
+            validator_data.delegations_open = true;
+            validator_data.total_stake = validator_data.total_stake.saturating_sub(amount);
+            Validators::<T>::insert(&validator, validator_data);
+            
+            delegator.total_staked = delegator.total_staked.saturating_sub(amount);
+            if delegator.delegations.is_empty() {
+                Delegators::<T>::remove(&who);
+            } else {
+                Delegators::<T>::insert(&who, delegator);
+            }
+            
+            Self::deposit_event(Event::DelegationWithdrawn(who, validator, amount));
+            
+            Ok(())
+        }
+        
+        /// Increase validator's self-stake.
+        ///
+        /// The dispatch origin must be Signed and the account must be a registered validator.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Two DB entries.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::increase_stake())]
+        pub fn increase_stake(
+            origin: OriginFor<T>,
+            #[pallet::compact] additional_amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            // Check if is a validator with bonded stake
+            ensure!(!Self::is_declared(&who), Error::<T>::NotBonded);
+            ensure!(Validators::<T>::contains_key(&who), Error::<T>::NotValidator);
+            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            
+            // Update validator's stake
+            validator.self_stake = validator.self_stake.saturating_add(additional_amount);
+            validator.total_stake = validator.total_stake.saturating_add(additional_amount);
+            
+            Validators::<T>::insert(&who, validator);
+            
+            // Lock the new cumulative self-stake, keeping any unbonding chunks locked
+            Self::update_stake_lock(&who);
+            
+            Self::deposit_event(Event::ValidatorStakeIncreased(who, additional_amount));
+            
+            Ok(())
+        }
+        
+        /// Decrease validator's self-stake.
+        ///
+        /// The dispatch origin must be Signed and the account must be a registered validator.
+        /// An active validator may only trim its stake: it must keep at least the minimum
+        /// validator stake and `MinSelfStakeRatio` of its total stake, and must wait
+        /// `StakeDecreaseCooldown` eras between decreases. Withdrawing everything requires
+        /// deregistering first, with no delegations left. Any remaining self-stake must keep the total stake within
+        /// `MaxStakeMultiplier` times it.
+        ///
+        /// The amount stays locked in `UnbondingStake` for `BondingDuration` eras and is then
+        /// unlocked with `withdraw_unbonded`.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Three DB entries.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::decrease_stake())]
+        pub fn decrease_stake(
+            origin: OriginFor<T>,
+            #[pallet::compact] amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            // Check if is a validator with bonded stake
+            ensure!(!Self::is_declared(&who), Error::<T>::NotBonded);
+            ensure!(Validators::<T>::contains_key(&who), Error::<T>::NotValidator);
+            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            
+            // Check if there's enough stake to withdraw
+            ensure!(amount <= validator.self_stake, Error::<T>::InsufficientStake);
+            
+            let current_era = Self::current_era();
+            let remaining = validator.self_stake.saturating_sub(amount);
+            let min_stake = Self::effective_min_validator_stake();
+            
+            if validator.is_active {
+                // Active validators may only trim their stake, at most once per cooldown
+                if let Some(last_decrease) = LastStakeDecrease::<T>::get(&who) {
+                    ensure!(
+                        current_era >= last_decrease.saturating_add(T::StakeDecreaseCooldown::get()),
+                        Error::<T>::StakeDecreaseInCooldown
+                    );
+                }
+                ensure!(remaining >= min_stake, Error::<T>::InsufficientStake);
+                
+                let remaining_total = validator.total_stake.saturating_sub(amount);
+                ensure!(
+                    Perbill::from_rational(remaining, remaining_total) >= T::MinSelfStakeRatio::get(),
+                    Error::<T>::SelfStakeRatioTooLow
+                );
+                
+                LastStakeDecrease::<T>::insert(&who, current_era);
+            } else {
+                ensure!(
+                    remaining >= min_stake || remaining == Zero::zero(),
+                    Error::<T>::InsufficientStake
+                );
+            }
+            
+            // Only a deregistered validator nobody delegates to may withdraw everything, so that
+            // no delegation is left behind a removed validator and `ValidatorCount` stays exact
+            if remaining.is_zero() {
+                ensure!(
+                    ValidatorStatuses::<T>::get(&who) == ValidatorStatus::Deregistered,
+                    Error::<T>::CannotWithdrawWhileActive
+                );
+                ensure!(
+                    validator.total_stake.saturating_sub(amount).is_zero(),
+                    Error::<T>::ValidatorHasDelegations
+                );
+            }
+            
+            // A remaining self-stake must still cover the delegations under the leverage cap
+            if !remaining.is_zero() {
+                ensure!(
+                    validator.total_stake.saturating_sub(amount) <= Self::max_total_stake(remaining),
+                    Error::<T>::SelfBondTooLow
+                );
+            }
+            
+            // Queue the amount for unbonding; the lock is left untouched until it is withdrawn
+            let unlock_era = current_era.saturating_add(T::BondingDuration::get());
+            UnbondingStake::<T>::try_mutate(&who, |chunks| chunks.try_push((unlock_era, amount)))
+                .map_err(|_| Error::<T>::NoMoreChunks)?;
+            
+            // Update validator's stake
+            validator.self_stake = validator.self_stake.saturating_sub(amount);
+            validator.total_stake = validator.total_stake.saturating_sub(amount);
+            
+            // If validator has withdrawn all stake, remove them
+            if validator.self_stake.is_zero() {
+                Validators::<T>::remove(&who);
+                ValidatorStatuses::<T>::remove(&who);
+                ValidatorPreferences::<T>::remove(&who);
+                LastStakeDecrease::<T>::remove(&who);
+                ValidatorMetadata::<T>::remove(&who);
+            } else {
+                Validators::<T>::insert(&who, validator);
+            }
+            
+            Self::deposit_event(Event::ValidatorStakeDecreased(who.clone(), amount));
+            Self::deposit_event(Event::Unbonded(who, amount, unlock_era));
+            
+            Ok(())
+        }
+        
+        /// Unlock all of the caller's unbonding self-stake and undelegated stake whose bonding
+        /// period has ended.
+        ///
+        /// The dispatch origin must be Signed. The `stakeatls` lock is reduced to cover only the
+        /// self-stake still bonded or unbonding, and matured delegation chunks are unreserved.
+        /// Nothing can be withdrawn while a deferred slash affecting the caller is pending, as
+        /// the slash may still take from the unbonding chunks.
+        ///
+        /// # <weight>
+        /// - O(C) where C is the number of pending unbonding chunks (bounded).
+        /// - Five DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn withdraw_unbonded(
+            origin: OriginFor<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!Self::has_pending_slash(&who), Error::<T>::SlashPending);
+            
+            let current_era = Self::current_era();
+            let withdraw_matured = |maybe_chunks: &mut Option<BoundedVec<(EraIndex, BalanceOf<T>), T::MaxUnlockingChunks>>| {
+                let mut withdrawn: BalanceOf<T> = Zero::zero();
+                if let Some(chunks) = maybe_chunks {
+                    chunks.retain(|(unlock_era, amount)| {
+                        if *unlock_era <= current_era {
+                            withdrawn = withdrawn.saturating_add(*amount);
+                            false
+                        } else {
+                            true
+                        }
+                    });
+                    if chunks.is_empty() {
+                        *maybe_chunks = None;
+                    }
+                }
+                withdrawn
             };
             
-            // Store validator
-            Validators::<T>::insert(&who, validator);
+            let withdrawn_stake = UnbondingStake::<T>::mutate_exists(&who, withdraw_matured);
+            let withdrawn_delegations = UnbondingDelegations::<T>::mutate_exists(&who, withdraw_matured);
+            let withdrawn = withdrawn_stake.saturating_add(withdrawn_delegations);
+            
+            ensure!(!withdrawn.is_zero(), Error::<T>::CannotWithdrawBeforeBondingPeriod);
+            
+            Self::update_stake_lock(&who);
+            T::Currency::unreserve_named(&DELEGATION_RESERVE_ID, &who, withdrawn_delegations);
+            
+            Self::deposit_event(Event::Withdrawn(who, withdrawn));
+            
+            Ok(())
+        }
+        
+        /// Return up to `amount` of the caller's unbonding self-stake to its bonded self-stake.
+        ///
+        /// The dispatch origin must be Signed and the account must be a validator with
+        /// unbonding chunks. The newest chunks are rebonded first; a partially rebonded chunk
+        /// keeps its unlock era. The funds stay locked throughout.
+        ///
+        /// # <weight>
+        /// - O(C) where C is the number of pending unbonding chunks (bounded).
+        /// - Two DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn rebond(
+            origin: OriginFor<T>,
+            #[pallet::compact] amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            ensure!(!Self::is_declared(&who), Error::<T>::NotBonded);
+            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            
+            let mut chunks = UnbondingStake::<T>::get(&who);
+            ensure!(!chunks.is_empty(), Error::<T>::NoUnlockChunk);
+            
+            let mut rebonded: BalanceOf<T> = Zero::zero();
+            while let Some((unlock_era, chunk)) = chunks.pop() {
+                let remaining = amount.saturating_sub(rebonded);
+                if chunk > remaining {
+                    // Only part of this chunk is needed; the rest keeps unbonding
+                    let _ = chunks.try_push((unlock_era, chunk.saturating_sub(remaining)));
+                    rebonded = amount;
+                    break;
+                }
+                
+                rebonded = rebonded.saturating_add(chunk);
+                if rebonded == amount {
+                    break;
+                }
+            }
+            
+            if chunks.is_empty() {
+                UnbondingStake::<T>::remove(&who);
+            } else {
+                UnbondingStake::<T>::insert(&who, chunks);
+            }
+            
+            validator.self_stake = validator.self_stake.saturating_add(rebonded);
+            validator.total_stake = validator.total_stake.saturating_add(rebonded);
+            Validators::<T>::insert(&who, validator);
+            
+            Self::deposit_event(Event::Rebonded(who, rebonded));
+            
+            Ok(())
+        }
+        
+        /// Resynchronise era-indexed state with the given block after a chain revert.
+        ///
+        /// The dispatch origin must be Root. See [`Pallet::resync_era_from_block`].
+        ///
+        /// # <weight>
+        /// - O(E) where E is the number of eras being rolled back.
+        /// # </weight>
+        #[pallet::weight(100_000)]
+        pub fn resync_era(
+            origin: OriginFor<T>,
+            block: T::BlockNumber,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            
+            let era = Self::resync_era_from_block(block);
+            
+            Self::deposit_event(Event::EraResynced(era));
+            
+            Ok(())
+        }
+        
+        /// Remove a stale validator from storage.
+        ///
+        /// The dispatch origin must be Signed; anyone may reap. The validator must be deregistered,
+        /// have been inactive for more than `BondingDuration` eras, and have no stake left: no
+        /// delegations, no self-stake and nothing still unbonding, e.g. after a full slash. A
+        /// validator with a deferred slash pending cannot be reaped.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Ten DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn reap_stale_validator(
+            origin: OriginFor<T>,
+            validator: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+            let validator = T::Lookup::lookup(validator)?;
+            
+            let validator_data = Validators::<T>::get(&validator).ok_or(Error::<T>::NotValidator)?;
+            ensure!(!Self::has_pending_slash(&validator), Error::<T>::SlashPending);
+            
+            // Only deregistered validators with nothing left bonded or unbonding can be reaped
+            ensure!(
+                ValidatorStatuses::<T>::get(&validator) == ValidatorStatus::Deregistered,
+                Error::<T>::ValidatorNotStale
+            );
+            ensure!(
+                validator_data.total_stake.is_zero() && validator_data.self_stake.is_zero(),
+                Error::<T>::ValidatorNotStale
+            );
+            ensure!(
+                Self::chunks_total(&UnbondingStake::<T>::get(&validator)).is_zero(),
+                Error::<T>::ValidatorNotStale
+            );
+            
+            // The validator must have been inactive for longer than the bonding period
+            let stale_after = validator_data.last_active_era.saturating_add(T::BondingDuration::get());
+            ensure!(Self::current_era() > stale_after, Error::<T>::ValidatorNotStale);
+            
+            Validators::<T>::remove(&validator);
+            ValidatorStatuses::<T>::remove(&validator);
+            ValidatorPreferences::<T>::remove(&validator);
+            LastStakeDecrease::<T>::remove(&validator);
+            ValidatorNominators::<T>::remove(&validator);
+            ValidatorMetadata::<T>::remove(&validator);
+            Jailed::<T>::remove(&validator);
+            // The reward destination is shared with the account's delegations, if it has any
+            if !Delegators::<T>::contains_key(&validator) {
+                Payee::<T>::remove(&validator);
+            }
+            
+            // Drop whatever lock is left now that nothing is bonded
+            Self::update_stake_lock(&validator);
+            
+            Self::deposit_event(Event::ValidatorReaped(validator));
+            
+            Ok(())
+        }
+        
+        /// Release the caller from jail once the jail period has passed.
+        ///
+        /// The dispatch origin must be Signed and the account must be a jailed validator whose
+        /// self-stake has been topped up to at least `MinValidatorStake`. A slashed validator is
+        /// restored to `Active` and can be selected again.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Three DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn unjail(
+            origin: OriginFor<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            let release_era = Jailed::<T>::get(&who).ok_or(Error::<T>::NotJailed)?;
+            
+            ensure!(Self::current_era() >= release_era, Error::<T>::StillJailed);
+            ensure!(
+                validator.self_stake >= Self::effective_min_validator_stake(),
+                Error::<T>::InsufficientStake
+            );
+            
+            Jailed::<T>::remove(&who);
+            
+            validator.is_active = true;
+            Validators::<T>::insert(&who, validator);
+            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Active);
+            
+            Self::deposit_event(Event::ValidatorUnjailed(who));
+            
+            Ok(())
+        }
+        
+        /// Retry a reward payout that previously failed.
+        ///
+        /// The dispatch origin must be Signed; anyone may retry on behalf of the owed account.
+        /// If `MaxTotalSupply` only allows part of the owed amount to be minted, the remainder
+        /// stays in `FailedPayouts` for a later retry.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Moderate complexity.
+        /// - O(1).
+        /// - Two DB entries.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn retry_payout(
+            origin: OriginFor<T>,
+            who: <T::Lookup as StaticLookup>::Source,
+            era: EraIndex,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+            let who = T::Lookup::lookup(who)?;
+            
+            let owed = FailedPayouts::<T>::get(era, &who);
+            ensure!(!owed.is_zero(), Error::<T>::NoFailedPayout);
+            
+            // Near the supply cap only part of the debt may be minted; the rest stays owed
+            let minted = Self::mint_capped(&Self::payee_account(&who), owed)?;
+            let remaining = owed.saturating_sub(minted);
+            if remaining.is_zero() {
+                FailedPayouts::<T>::remove(era, &who);
+            } else {
+                FailedPayouts::<T>::insert(era, &who, remaining);
+            }
+            
+            Self::deposit_event(Event::PayoutRetried(who, era, minted));
+            
+            Ok(())
+        }
+        
+        /// Set the caller's validator commission.
+        ///
+        /// The dispatch origin must be Signed and the account must be a registered validator.
+        /// The commission may not exceed `MaxCommission` and applies to era rewards computed
+        /// from now on.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn set_commission(
+            origin: OriginFor<T>,
+            new_commission: Perbill,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            ensure!(new_commission <= T::MaxCommission::get(), Error::<T>::CommissionTooHigh);
+            
+            Validators::<T>::try_mutate(&who, |maybe_validator| -> DispatchResult {
+                let validator = maybe_validator.as_mut().ok_or(Error::<T>::NotValidator)?;
+                validator.commission = new_commission;
+                Ok(())
+            })?;
+            
+            Self::deposit_event(Event::CommissionSet(who, new_commission));
+            
+            Ok(())
+        }
+        
+        /// Set the caller's validator preferences.
+        ///
+        /// The dispatch origin must be Signed and the account must be a registered or declared
+        /// validator. A `min_delegation` below `MinDelegationStake` has no effect.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn set_validator_prefs(
+            origin: OriginFor<T>,
+            prefs: ValidatorPrefs<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            ensure!(
+                Validators::<T>::contains_key(&who) || Self::is_declared(&who),
+                Error::<T>::NotValidator
+            );
+            
+            ValidatorPreferences::<T>::insert(&who, prefs.clone());
+            
+            Self::deposit_event(Event::ValidatorPrefsSet(who, prefs));
+            
+            Ok(())
+        }
+        
+        /// Set the caller's validator metadata, replacing any previous metadata.
+        ///
+        /// The dispatch origin must be Signed and the account must be a registered or `Declared`
+        /// validator.
+        /// Each field is bounded by its `MaxValidator*Length` constant.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn set_validator_metadata(
+            origin: OriginFor<T>,
+            metadata: ValidatorDetailsOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            
+            ensure!(
+                Self::is_declared(&who) ||
+                    (Validators::<T>::contains_key(&who) &&
+                        Self::validator_status(&who) != ValidatorStatus::Deregistered),
+                Error::<T>::NotValidator
+            );
+            
+            ValidatorMetadata::<T>::insert(&who, metadata);
+            
+            Self::deposit_event(Event::ValidatorMetadataUpdated(who));
+            
+            Ok(())
+        }
+        
+        /// Pay out the rewards of `validator` and its delegators for `era`.
+        ///
+        /// The dispatch origin must be Signed; anyone may trigger a payout. Each
+        /// `(era, validator)` pair can be paid out once.
+        ///
+        /// # <weight>
+        /// - O(N) where N is the number of delegators paid, bounded by `MaxNominatorsPerValidator`.
+        /// - Charged for the bound up front and refunded to the delegators actually paid.
+        /// # </weight>
+        #[pallet::weight(Pallet::<T>::payout_stakers_weight(T::MaxNominatorsPerValidator::get()))]
+        pub fn payout_stakers(
+            origin: OriginFor<T>,
+            validator: <T::Lookup as StaticLookup>::Source,
+            era: EraIndex,
+        ) -> DispatchResultWithPostInfo {
+            ensure_signed(origin)?;
+            let validator = T::Lookup::lookup(validator)?;
+            
+            ensure!(!ClaimedRewards::<T>::get(era, &validator), Error::<T>::RewardsAlreadyClaimed);
+            let reward = ErasValidatorReward::<T>::get(era, &validator)
+                .ok_or(Error::<T>::NoRewardsForEra)?;
+            
+            ClaimedRewards::<T>::insert(era, &validator, true);
+            
+            let (total_paid, delegators_paid) = Self::do_payout_stakers(&validator, era, reward);
+            
+            Self::deposit_event(Event::StakersPaidOut(validator, era, total_paid));
+            
+            Ok(Some(Self::payout_stakers_weight(delegators_paid)).into())
+        }
+        
+        /// Pay out every pending reward of the validators the caller delegates to.
+        ///
+        /// The dispatch origin must be Signed and the account must be a delegator. Each
+        /// `(validator, era)` payout since the delegation became effective, and at most
+        /// `HistoryDepth` eras back, is claimed as with `payout_stakers`. At most `max_calls`
+        /// `(validator, era)` pairs are visited, whether or not they had a payout pending. The
+        /// number claimed is reported in `AllClaimed`.
+        ///
+        /// # <weight>
+        /// - O(max_calls * N) where N is bounded by `MaxNominatorsPerValidator`.
+        /// - Refunded to the pairs visited.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::claim_all(*max_calls))]
+        pub fn claim_all(
+            origin: OriginFor<T>,
+            max_calls: u32,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let delegator = Delegators::<T>::get(&who).ok_or(Error::<T>::NotDelegator)?;
+            
+            let current_era = Self::current_era();
+            // Older rewards have been swept and can no longer be claimed
+            let oldest_era = current_era.saturating_sub(T::HistoryDepth::get());
+            let mut visited: u32 = 0;
+            let mut claimed: u32 = 0;
+            
+            'validators: for (validator, delegation) in delegator.delegations.iter() {
+                for era in delegation.since_era.max(oldest_era)..=current_era {
+                    if visited >= max_calls {
+                        break 'validators;
+                    }
+                    visited = visited.saturating_add(1);
+                    
+                    if ClaimedRewards::<T>::get(era, validator) {
+                        continue;
+                    }
+                    let reward = match ErasValidatorReward::<T>::get(era, validator) {
+                        Some(reward) => reward,
+                        None => continue,
+                    };
+                    
+                    ClaimedRewards::<T>::insert(era, validator, true);
+                    let (total_paid, _) = Self::do_payout_stakers(validator, era, reward);
+                    Self::deposit_event(Event::StakersPaidOut(validator.clone(), era, total_paid));
+                    
+                    claimed = claimed.saturating_add(1);
+                }
+            }
+            
+            Self::deposit_event(Event::AllClaimed(who, claimed));
             
-            // Update validator status
-            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Active);
+            Ok(Some(T::WeightInfo::claim_all(visited)).into())
+        }
+        
+        /// Recompute a delegator's `total_staked` from its delegations.
+        ///
+        /// The dispatch origin must be Signed; anyone may reconcile any delegator.
+        /// `DelegatorReconciled` is emitted if the stored total had drifted.
+        ///
+        /// # <weight>
+        /// - O(D) where D is the number of delegations, bounded by `MaxDelegationsPerDelegator`.
+        /// - One DB read and at most one DB write.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn reconcile_delegator(
+            origin: OriginFor<T>,
+            who: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+            let who = T::Lookup::lookup(who)?;
+            let mut delegator = Delegators::<T>::get(&who).ok_or(Error::<T>::NotDelegator)?;
             
-            // Update validator count
-            let count = ValidatorCount::<T>::get().saturating_add(1);
-            ValidatorCount::<T>::put(count);
+            let total_staked = delegator.delegations
+                .values()
+                .fold(Zero::zero(), |total: BalanceOf<T>, delegation| total.saturating_add(delegation.amount));
             
-            Self::deposit_event(Event::ValidatorRegistered(who));
+            if delegator.total_staked != total_staked {
+                let old_total = delegator.total_staked;
+                delegator.total_staked = total_staked;
+                Delegators::<T>::insert(&who, delegator);
+                
+                Self::deposit_event(Event::DelegatorReconciled(who, old_total, total_staked));
+            }
             
             Ok(())
         }
         
-        /// Deregister as a validator.
+        /// Set where the caller's staking rewards are paid.
         ///
-        /// The dispatch origin must be Signed and the account must be currently registered as a validator.
+        /// The dispatch origin must be Signed and the account must be a validator or delegator.
         ///
         /// # <weight>
         /// - Independent of the arguments. Moderate complexity.
         /// - O(1).
-        /// - Three DB entries.
+        /// - One DB entry.
         /// # </weight>
         #[pallet::weight(10_000)]
-        pub fn deregister_validator(
+        pub fn set_payee(
             origin: OriginFor<T>,
+            payee: RewardDestination<T::AccountId>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
             
-            // Check if is a validator
-            ensure!(Validators::<T>::contains_key(&who), Error::<T>::NotValidator);
+            ensure!(
+                Validators::<T>::contains_key(&who) || Delegators::<T>::contains_key(&who),
+                Error::<T>::NotStaker
+            );
             
-            // Check if validator is active and can be deregistered
-            let validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            Payee::<T>::insert(&who, payee.clone());
             
-            // Update validator status
-            ValidatorStatuses::<T>::insert(&who, ValidatorStatus::Deregistered);
+            Self::deposit_event(Event::PayeeSet(who, payee));
             
-            // Update validator data
-            let updated_validator = Validator {
-                is_active: false,
-                ..validator
+            Ok(())
+        }
+        
+        /// Set whether the rewards of the caller's delegation to `validator` are compounded.
+        ///
+        /// The dispatch origin must be Signed and the account must have delegated to the
+        /// validator. A compounding delegation's rewards are added to it whatever the caller's
+        /// `Payee` is.
+        ///
+        /// # <weight>
+        /// - O(D) where D is the number of delegations, bounded by `MaxDelegationsPerDelegator`.
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::set_compound())]
+        pub fn set_compound(
+            origin: OriginFor<T>,
+            validator: <T::Lookup as StaticLookup>::Source,
+            compound: bool,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let validator = T::Lookup::lookup(validator)?;
+            
+            Delegators::<T>::try_mutate(&who, |maybe_delegator| -> DispatchResult {
+                let delegation = maybe_delegator
+                    .as_mut()
+                    .and_then(|delegator| delegator.delegations.get_mut(&validator))
+                    .ok_or(Error::<T>::NotDelegator)?;
+                delegation.compound = compound;
+                Ok(())
+            })?;
+            
+            Self::deposit_event(Event::DelegationCompoundSet(who, validator, compound));
+            
+            Ok(())
+        }
+        
+        /// Slash `proportion` of a validator's self-stake and of every delegation backing it.
+        ///
+        /// The dispatch origin must be Root. The slash is applied at the start of the era
+        /// `SlashDeferDuration` eras from now, and can be cancelled with `cancel_deferred_slash`
+        /// until then; with no defer duration it is applied immediately. Only the delegators
+        /// backing the validator now are slashed.
+        ///
+        /// The slash takes `proportion` of the self-stake and of each delegation as reported now.
+        /// Whatever of those was unbonded in the meantime is taken from the unbonding chunks,
+        /// newest first, and the affected accounts cannot withdraw until the slash is applied.
+        ///
+        /// When applied, the slashed funds go to `Slash`, the self-stake lock and the delegators'
+        /// reserves shrink by the amounts actually taken, and the validator is jailed, marked
+        /// `Slashed` and inactive, excluding it from future selection.
+        ///
+        /// A slashed validator cannot `unjail` or `reactivate`. To validate again it must
+        /// `deregister_validator` and leave storage, either by decreasing its self-stake to zero
+        /// or by being reaped with `reap_stale_validator`, and then `register_validator` anew.
+        ///
+        /// # <weight>
+        /// - O(N) where N is the number of delegators, bounded by `MaxNominatorsPerValidator`.
+        /// # </weight>
+        #[pallet::weight(T::WeightInfo::slash_validator(T::MaxNominatorsPerValidator::get()))]
+        pub fn slash_validator(
+            origin: OriginFor<T>,
+            validator: <T::Lookup as StaticLookup>::Source,
+            proportion: Perbill,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            let validator = T::Lookup::lookup(validator)?;
+            
+            ensure!(Validators::<T>::contains_key(&validator), Error::<T>::NotValidator);
+            
+            let slash = UnappliedSlash {
+                validator: validator.clone(),
+                proportion,
+                own: Validators::<T>::get(&validator).map_or_else(Zero::zero, |v| v.self_stake),
+                delegators: BoundedVec::truncate_from(Self::nominators_of(&validator)),
             };
-            Validators::<T>::insert(&who, updated_validator);
             
-            // Update validator count
-            let count = ValidatorCount::<T>::get().saturating_sub(1);
-            ValidatorCount::<T>::put(count);
+            let defer_duration = T::SlashDeferDuration::get();
+            if defer_duration.is_zero() {
+                Self::apply_slash(slash);
+                return Ok(());
+            }
+            
+            let apply_era = Self::current_era().saturating_add(defer_duration);
+            UnappliedSlashes::<T>::try_mutate(apply_era, |slashes| slashes.try_push(slash))
+                .map_err(|_| Error::<T>::TooManyUnappliedSlashes)?;
+            
+            Self::deposit_event(Event::SlashDeferred(validator, proportion, apply_era));
+            
+            Ok(())
+        }
+        
+        /// Cancel the deferred slashes of `validator` due at the start of `era`.
+        ///
+        /// The dispatch origin must be `SlashCancelOrigin`.
+        ///
+        /// # <weight>
+        /// - O(S) where S is the number of slashes deferred to the era, bounded by `MaxCandidates`.
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn cancel_deferred_slash(
+            origin: OriginFor<T>,
+            era: EraIndex,
+            validator: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResult {
+            T::SlashCancelOrigin::ensure_origin(origin)?;
+            let validator = T::Lookup::lookup(validator)?;
+            
+            UnappliedSlashes::<T>::try_mutate_exists(era, |maybe_slashes| -> DispatchResult {
+                let slashes = maybe_slashes.as_mut().ok_or(Error::<T>::NoUnappliedSlash)?;
+                let before = slashes.len();
+                slashes.retain(|slash| slash.validator != validator);
+                ensure!(slashes.len() < before, Error::<T>::NoUnappliedSlash);
+                
+                if slashes.is_empty() {
+                    *maybe_slashes = None;
+                }
+                Ok(())
+            })?;
+            
+            Self::deposit_event(Event::DeferredSlashCancelled(validator, era));
+            
+            Ok(())
+        }
+        
+        /// Set the minimum validator self-stake, on top of the `MinValidatorStake` floor.
+        ///
+        /// The dispatch origin must be Root. Validators below the new minimum are moved to
+        /// `InsufficientStake` at the next era rotation.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn set_minimum_validator_stake(
+            origin: OriginFor<T>,
+            #[pallet::compact] amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            
+            MinimumValidatorStake::<T>::put(amount);
+            
+            Self::deposit_event(Event::MinimumValidatorStakeSet(amount));
+            
+            Ok(())
+        }
+        
+        /// Set the minimum number of blocks between eras.
+        ///
+        /// The dispatch origin must be Root and `blocks` must lie within
+        /// `MinEraDuration..=MaxEraDuration`. The new duration applies from the next era, so the
+        /// current era is neither cut short nor extended.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn set_era_duration(
+            origin: OriginFor<T>,
+            blocks: T::BlockNumber,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            
+            ensure!(
+                blocks >= T::MinEraDuration::get() && blocks <= T::MaxEraDuration::get(),
+                Error::<T>::InvalidEraDuration
+            );
+            
+            NextEraDuration::<T>::put(blocks);
+            
+            Self::deposit_event(Event::EraDurationSet(blocks));
+            
+            Ok(())
+        }
+        
+        /// Start a new era on the next block, however long the current era has lasted.
+        ///
+        /// The dispatch origin must be Root. The new era's validator set takes over at the next
+        /// session boundary. This also lifts a freeze set with `force_no_era`; once the forced
+        /// era has started, eras rotate on `CurrentEraDuration` again.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn force_new_era(
+            origin: OriginFor<T>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            
+            ForceEra::<T>::put(Forcing::ForceNew);
+            
+            Self::deposit_event(Event::ForceEraSet(Forcing::ForceNew));
+            
+            Ok(())
+        }
+        
+        /// Stop rotating eras until `force_new_era` is called.
+        ///
+        /// The dispatch origin must be Root. The current validator set stays in place.
+        ///
+        /// # <weight>
+        /// - Independent of the arguments. Low complexity.
+        /// - O(1).
+        /// - One DB entry.
+        /// # </weight>
+        #[pallet::weight(10_000)]
+        pub fn force_no_era(
+            origin: OriginFor<T>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            
+            ForceEra::<T>::put(Forcing::ForceNone);
+            
+            Self::deposit_event(Event::ForceEraSet(Forcing::ForceNone));
+            
+            Ok(())
+        }
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            // A forced era starts at once rather than waiting for a session boundary
+            if Self::force_era() == Forcing::ForceNew {
+                Self::new_era(now);
+                return T::DbWeight::get().reads(1).saturating_add(T::WeightInfo::new_era(
+                    T::MaxCandidates::get(),
+                    T::MaxNominatorsPerValidator::get(),
+                ));
+            }
+            
+            T::DbWeight::get().reads(1)
+        }
+        
+        fn on_finalize(_n: T::BlockNumber) {
+            // No finalization logic needed for now
+        }
+        
+        fn integrity_test() {
+            assert!(
+                T::MinEraDuration::get() <= T::EraDuration::get() &&
+                    T::EraDuration::get() <= T::MaxEraDuration::get(),
+                "EraDuration must lie within MinEraDuration..=MaxEraDuration"
+            );
+            assert!(
+                T::MinActiveValidators::get() <= T::MaxValidatorsCount::get(),
+                "MinActiveValidators must not exceed MaxValidatorsCount"
+            );
+        }
+    }
+
+    // Additional implementation for the pallet
+    impl<T: Config> Pallet<T> {
+        /// Whether the current era has lasted at least `CurrentEraDuration` blocks as of block
+        /// `now`, unless `ForceEra` forces or freezes rotation.
+        pub fn era_elapsed(now: T::BlockNumber) -> bool {
+            match Self::force_era() {
+                Forcing::ForceNew => return true,
+                Forcing::ForceNone => return false,
+                Forcing::NotForcing => {},
+            }
+            
+            let current_era = Self::current_era();
+            let era_start_block = Self::era_start_block_number(current_era);
+            let expected_era_end = era_start_block.saturating_add(Self::current_era_duration());
+            
+            now >= expected_era_end
+        }
+        
+        /// Rotate to a new era starting at block `now` and return its index.
+        ///
+        /// Called from `SessionManager::new_session`, so that eras start on a session boundary,
+        /// and from `on_initialize` for an era forced with `force_new_era`, whose set is then
+        /// handed to the next session.
+        pub(crate) fn new_era(now: T::BlockNumber) -> EraIndex {
+            let current_era = Self::current_era();
+            
+            // Fix the ending era's reward: its issuance plus the fees collected during it
+            Self::add_issuance_to_reward(current_era);
+            Self::add_fees_to_reward(current_era);
+            
+            // A forced rotation only applies to this era
+            if Self::force_era() == Forcing::ForceNew {
+                ForceEra::<T>::put(Forcing::NotForcing);
+            }
+            
+            // Start new era
+            let new_era = current_era.saturating_add(1);
+            CurrentEra::<T>::put(new_era);
+            EraStartBlockNumber::<T>::insert(new_era, now);
+            if let Some(duration) = NextEraDuration::<T>::take() {
+                CurrentEraDuration::<T>::put(duration);
+            }
+            
+            // Update validator reputation scores
+            Self::update_reputation_scores();
+            
+            // Apply the slashes whose dispute window has passed before selection
+            Self::apply_unapplied_slashes(new_era);
+            
+            // Exclude validators whose self-stake fell below the minimum
+            Self::enforce_minimum_stake();
+            
+            // Select validators for the new era, exactly once per era
+            let validators = Self::select_validators();
+            T::OnNewValidatorSet::on_new_validator_set(new_era, &validators);
+            
+            // Make the rewards of the previous era (with a delay) claimable
+            if current_era >= T::RewardPaymentDelay::get() {
+                let reward_era = current_era.saturating_sub(T::RewardPaymentDelay::get());
+                let _ = Self::compute_era_rewards(reward_era);
+            }
+            
+            // Drop the rewards nobody claimed in time and warn about the next ones to go
+            Self::sweep_expired_rewards(new_era);
+            
+            Self::deposit_event(Event::NewEra(new_era));
+            
+            new_era
+        }
+        
+        /// Recompute `CurrentEra` as of block `n` from the recorded `EraStartBlockNumber` entries.
+        ///
+        /// Every era that started after `n` is discarded together with its era-indexed data, so
+        /// that reverting blocks (e.g. via the node's `revert` subcommand on a testnet) leaves the
+        /// staking state consistent with the new chain head. Returns the resynchronised era.
+        pub fn resync_era_from_block(n: T::BlockNumber) -> EraIndex {
+            let current_era = Self::current_era();
+            
+            // Find the latest era that had already started at block `n`; era 0 always has
+            let mut era = current_era;
+            while era > 0 && Self::era_start_block_number(era) > n {
+                era = era.saturating_sub(1);
+            }
+            
+            // Clear data belonging to the eras that no longer exist
+            for stale_era in era.saturating_add(1)..=current_era {
+                EraStartBlockNumber::<T>::remove(stale_era);
+                ErasStartSessionIndex::<T>::remove(stale_era);
+                ErasValidatorList::<T>::remove(stale_era);
+                ErasTotalStake::<T>::remove(stale_era);
+                ErasReward::<T>::remove(stale_era);
+                let _ = ErasValidatorReward::<T>::clear_prefix(stale_era, u32::MAX, None);
+                let _ = ClaimedRewards::<T>::clear_prefix(stale_era, u32::MAX, None);
+                ErasBlockCount::<T>::remove(stale_era);
+                let _ = ErasAuthoredBlocks::<T>::clear_prefix(stale_era, u32::MAX, None);
+                ErasTotalRewardPoints::<T>::remove(stale_era);
+                let _ = ErasRewardPoints::<T>::clear_prefix(stale_era, u32::MAX, None);
+                let _ = ErasStakers::<T>::clear_prefix(stale_era, u32::MAX, None);
+            }
+            
+            CurrentEra::<T>::put(era);
+            if Self::active_era() > era {
+                ActiveEra::<T>::put(era);
+            }
+            if Self::earliest_unclaimed_era() > era {
+                EarliestUnclaimedEra::<T>::put(era);
+            }
+            
+            era
+        }
+        
+        /// Select validators for the next era based on stake and reputation.
+        ///
+        /// At most `min(ValidatorsCount, MaxValidatorsCount)` validators are selected; if
+        /// `ValidatorsCount` exceeds the storage bound the selection is truncated to it. If fewer
+        /// than `MinActiveValidators` could be selected, the previous era's set is kept instead.
+        pub(crate) fn select_validators() -> BoundedVec<T::AccountId, T::MaxValidatorsCount> {
+            // Get the active validators, at most `MaxCandidates` of them
+            let max_candidates = T::MaxCandidates::get() as usize;
+            let mut validators: Vec<(T::AccountId, BalanceOf<T>, BalanceOf<T>)> =
+                Vec::with_capacity(max_candidates);
+            
+            for (validator_id, validator_data) in Validators::<T>::iter() {
+                if validators.len() >= max_candidates {
+                    break;
+                }
+                
+                // Only consider active validators that are not jailed
+                if validator_data.is_active && !Self::is_jailed(&validator_id) {
+                    let status = ValidatorStatuses::<T>::get(&validator_id);
+                    if status == ValidatorStatus::Active {
+                        // Calculate validator score as a combination of stake and reputation
+                        // Formula: score = (1 - reputation_weight) * stake + reputation_weight * reputation
+                        let reputation_weight = T::ReputationWeight::get();
+                        let stake_weight = Perbill::from_percent(100) - reputation_weight;
+                        
+                        let stake_score = stake_weight * validator_data.total_stake;
+                        let reputation_score = reputation_weight * validator_data.reputation.score;
+                        
+                        let total_score = stake_score + reputation_score;
+                        
+                        validators.push((validator_id, total_score, validator_data.total_stake));
+                    }
+                }
+            }
+            
+            // Sort validators by total score (descending)
+            validators.sort_by(|a, b| b.1.cmp(&a.1));
+            
+            // Select top N validators where N is ValidatorsCount, truncated to the storage bound
+            let count = T::ValidatorsCount::get().min(T::MaxValidatorsCount::get()) as usize;
+            
+            // Choose randomly among the candidates tied for the last slots, so that storage
+            // order (and thereby account ids) cannot decide the selection
+            if count > 0 && validators.len() > count {
+                let boundary = validators[count - 1].1;
+                let start = validators.iter().position(|(_, score, _)| *score == boundary).unwrap_or(0);
+                let end = validators.iter().rposition(|(_, score, _)| *score == boundary).unwrap_or(0) + 1;
+                
+                if end > count {
+                    Self::shuffle(&mut validators[start..end]);
+                }
+            }
+            let mut selected = BoundedVec::<T::AccountId, T::MaxValidatorsCount>::truncate_from(
+                validators.iter()
+                    .take(count)
+                    .map(|(id, _, _)| id.clone())
+                    .collect::<Vec<_>>(),
+            );
+            
+            let current_era = Self::current_era();
+            
+            // Keep the last good set rather than shrinking below the liveness threshold, minus
+            // the validators that have since been reaped or slashed
+            if (selected.len() as u32) < T::MinActiveValidators::get() && current_era > 0 {
+                let mut previous = ErasValidatorList::<T>::get(current_era.saturating_sub(1));
+                previous.retain(|validator| {
+                    Validators::<T>::contains_key(validator) &&
+                        ValidatorStatuses::<T>::get(validator) != ValidatorStatus::Slashed
+                });
+                if !previous.is_empty() {
+                    selected = previous;
+                    Self::deposit_event(Event::ValidatorSetRetained(current_era));
+                }
+            }
+            
+            // Store selected validators for the current era
+            ErasValidatorList::<T>::insert(current_era, selected.clone());
+            
+            // Snapshot the exposure of every selected validator and the era's total stake
+            let _ = ErasStakers::<T>::clear_prefix(current_era, u32::MAX, None);
+            let mut total_stake: BalanceOf<T> = Zero::zero();
+            
+            for validator_id in selected.iter() {
+                Validators::<T>::mutate(validator_id, |maybe_validator| {
+                    if let Some(validator) = maybe_validator {
+                        validator.last_active_era = current_era;
+                        
+                        let exposure = Self::exposure_of(validator_id, validator.self_stake, current_era);
+                        total_stake = total_stake.saturating_add(exposure.total);
+                        ErasStakers::<T>::insert(current_era, validator_id, exposure);
+                    }
+                });
+            }
             
-            // Note: We don't remove the lock on the stake here.
-            // The stake will be unlocked after the bonding period.
+            ErasTotalStake::<T>::insert(current_era, total_stake);
             
-            Self::deposit_event(Event::ValidatorDeregistered(who));
+            selected
+        }
+        
+        /// Shuffle `items` with a Fisher-Yates shuffle seeded from `T::Randomness`.
+        fn shuffle<I>(items: &mut [I]) {
+            let (seed, _) = T::Randomness::random(b"atlas_validator_selection");
+            
+            for i in (1..items.len()).rev() {
+                let hash = T::Hashing::hash_of(&(seed, i as u32));
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(&hash.as_ref()[..4]);
+                let j = u32::from_le_bytes(bytes) as usize % (i + 1);
+                items.swap(i, j);
+            }
+        }
+        
+        /// Evict the smallest delegation to `validator` to make room for a new delegation of
+        /// `amount`, failing with `TooManyDelegators` unless `amount` is larger.
+        ///
+        /// The evicted delegation is unreserved at once, and the delegator's entry is removed
+        /// once it has no delegations left.
+        fn evict_smallest_delegation(
+            validator: &T::AccountId,
+            validator_data: &mut Validator<T::AccountId, BalanceOf<T>>,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let (smallest, smallest_amount) = ValidatorNominators::<T>::get(validator)
+                .into_iter()
+                .filter_map(|nominator| {
+                    let delegated = Delegators::<T>::get(&nominator)?
+                        .delegations
+                        .get(validator)?
+                        .amount;
+                    Some((nominator, delegated))
+                })
+                .min_by_key(|(_, delegated)| *delegated)
+                .ok_or(Error::<T>::TooManyDelegators)?;
+            ensure!(amount > smallest_amount, Error::<T>::TooManyDelegators);
+            
+            Delegators::<T>::mutate_exists(&smallest, |maybe_delegator| {
+                if let Some(delegator) = maybe_delegator {
+                    delegator.delegations.remove(validator);
+                    delegator.total_staked = delegator.total_staked.saturating_sub(smallest_amount);
+                    
+                    if delegator.delegations.is_empty() {
+                        *maybe_delegator = None;
+                    }
+                }
+            });
+            T::Currency::unreserve_named(&DELEGATION_RESERVE_ID, &smallest, smallest_amount);
+            ValidatorNominators::<T>::mutate(validator, |nominators| {
+                nominators.retain(|n| *n != smallest);
+            });
+            validator_data.total_stake = validator_data.total_stake.saturating_sub(smallest_amount);
+            
+            Self::deposit_event(Event::DelegationEvicted(smallest, validator.clone(), smallest_amount));
             
             Ok(())
         }
         
-        /// Delegate tokens to a validator.
-        ///
-        /// The dispatch origin must be Signed.
+        /// Build the exposure of `validator` for `era` from its self-stake and the delegations
+        /// effective in that era, as listed by the `ValidatorNominators` index.
+        fn exposure_of(
+            validator: &T::AccountId,
+            own: BalanceOf<T>,
+            era: EraIndex,
+        ) -> Exposure<T::AccountId, BalanceOf<T>, T::MaxNominatorsPerValidator> {
+            let mut total = own;
+            let mut delegations = BoundedVec::default();
+            
+            for nominator in ValidatorNominators::<T>::get(validator) {
+                let delegation = Delegators::<T>::get(&nominator)
+                    .and_then(|delegator| delegator.delegations.get(validator).cloned());
+                
+                if let Some(delegation) = delegation {
+                    // Delegations and top-ups made during the previous era only count from this
+                    // one onwards. `ValidatorNominators` shares the bound, so every delegation fits
+                    let value = delegation.effective_amount(era);
+                    if !value.is_zero() {
+                        let exposure = IndividualExposure { who: nominator, value };
+                        if delegations.try_push(exposure).is_ok() {
+                            total = total.saturating_add(value);
+                        }
+                    }
+                }
+            }
+            
+            Exposure { own, total, delegations }
+        }
+        
+        /// Calculate the reward of every validator of an era, to be paid out with
+        /// `payout_stakers`.
         ///
-        /// # <weight>
-        /// - Independent of the arguments. Moderate complexity.
-        /// - O(1).
-        /// - Three DB entries.
-        /// # </weight>
-        #[pallet::weight(10_000)]
-        pub fn delegate(
-            origin: OriginFor<T>,
-            validator: <T::Lookup as StaticLookup>::Source,
-            #[pallet::compact] amount: BalanceOf<T>,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-            let validator = T::Lookup::lookup(validator)?;
+        /// The era reward is split by the share of the era's reward points each validator
+        /// earned; `payout_stakers` then splits each validator's slice by stake. The reward of
+        /// each validator and its commission at this point are recorded in
+        /// `ErasValidatorReward`. Whatever part of the era reward is not allocated to any
+        /// validator goes to `DustRemoval`, including the whole reward of an era without
+        /// validators or reward points.
+        fn compute_era_rewards(era: EraIndex) -> DispatchResult {
+            // Check if rewards for this era are available
+            let era_reward = ErasReward::<T>::get(era).ok_or(Error::<T>::NoRewardsForEra)?;
             
-            // Check if validator exists and is active
-            ensure!(Validators::<T>::contains_key(&validator), Error::<T>::NotValidator);
-            let mut validator_data = Validators::<T>::get(&validator).ok_or(Error::<T>::NotValidator)?;
-            ensure!(validator_data.is_active, Error::<T>::ValidatorNotActive);
+            // Get validators for this era
+            let validators = ErasValidatorList::<T>::get(era);
             
-            // Check minimum delegation stake
-            ensure!(amount >= T::MinDelegationStake::get(), Error::<T>::InsufficientDelegationStake);
+            // Get total reward points for this era
+            let total_points = ErasTotalRewardPoints::<T>::get(era);
             
-            // Update or create delegator
-            if Delegators::<T>::contains_key(&who) {
-                let mut delegator = Delegators::<T>::get(&who).unwrap();
+            // If nobody earned anything, none of the reward can be allocated
+            if validators.is_empty() || total_points.is_zero() {
+                Self::remove_dust(era_reward);
+                ErasReward::<T>::remove(era);
+                return Ok(());
+            }
+            
+            let mut reward_remainder = era_reward;
+            let mut total_allocated: BalanceOf<T> = Zero::zero();
+            
+            // For each validator
+            for validator_id in validators.iter() {
+                // Calculate validator's share of rewards based on the work it did
+                let validator_points = ErasRewardPoints::<T>::get(era, validator_id);
+                let validator_points_ratio = Perbill::from_rational(validator_points, total_points);
+                let validator_reward = validator_points_ratio * era_reward;
                 
-                // Check maximum delegations
-                let max_delegations = T::MaxDelegationsPerDelegator::get();
-                let delegation_count = delegator.delegations.len() as u32;
+                // If validator reward is zero, skip
+                if validator_reward.is_zero() {
+                    continue;
+                }
                 
-                let existing_delegation_idx = delegator.delegations.iter().position(|(v, _)| *v == validator);
+                // Get the reputation adjustment for rewards
+                // Higher reputation means higher rewards
+                let (reputation, commission) = match Validators::<T>::get(validator_id) {
+                    Some(v) => (v.reputation.score, v.commission),
+                    None => (Zero::zero(), Perbill::zero()),
+                };
                 
-                if let Some(idx) = existing_delegation_idx {
-                    // Update existing delegation
-                    let current_amount = delegator.delegations[idx].1;
-                    delegator.delegations[idx].1 = current_amount.saturating_add(amount);
-                    delegator.total_staked = delegator.total_staked.saturating_add(amount);
-                } else {
-                    // Add new delegation
-                    ensure!(delegation_count < max_delegations, Error::<T>::TooManyDelegations);
-                    delegator.delegations.push((validator.clone(), amount));
-                    delegator.total_staked = delegator.total_staked.saturating_add(amount);
-                }
+                // We'll use a simple linear reputation adjustment for now
+                // A more sophisticated model could be implemented
+                let reputation_factor = Perbill::from_rational(reputation, 100u32.into());
+                let reputation_bonus = Perbill::from_percent(10) * reputation_factor * validator_reward;
+                let adjusted_validator_reward = validator_reward.saturating_add(reputation_bonus);
                 
-                Delegators::<T>::insert(&who, delegator);
-            } else {
-                // Create new delegator
-                let delegator = Delegator {
-                    account: who.clone(),
-                    delegations: vec![(validator.clone(), amount)],
-                    total_staked: amount,
+                // Ensure we don't exceed total reward
+                let actual_validator_reward = if adjusted_validator_reward > reward_remainder {
+                    reward_remainder
+                } else {
+                    adjusted_validator_reward
                 };
                 
-                Delegators::<T>::insert(&who, delegator);
+                reward_remainder = reward_remainder.saturating_sub(actual_validator_reward);
+                
+                if actual_validator_reward.is_zero() {
+                    continue;
+                }
+                
+                total_allocated = total_allocated.saturating_add(actual_validator_reward);
+                ErasValidatorReward::<T>::insert(era, validator_id, EraValidatorReward {
+                    amount: actual_validator_reward,
+                    commission,
+                });
             }
             
-            // Lock tokens
-            T::Currency::set_lock(
-                LockIdentifier(*b"delgatls"),
-                &who,
-                amount,
-                WithdrawReasons::all(),
-            );
-            
-            // Update validator's total stake
-            validator_data.total_stake = validator_data.total_stake.saturating_add(amount);
-            Validators::<T>::insert(&validator, validator_data);
+            // Hand whatever was not allocated to any validator to the dust handler
+            Self::remove_dust(era_reward.saturating_sub(total_allocated));
             
-            Self::deposit_event(Event::DelegationCreated(who, validator, amount));
+            // Remove era reward once it has been allocated
+            ErasReward::<T>::remove(era);
             
             Ok(())
         }
         
-        /// Undelegate tokens from a validator.
-        ///
-        /// The dispatch origin must be Signed and the account must have delegated to the validator.
+        /// Sweep the eras that fall out of the history as `new_era` starts and warn about the
+        /// earliest era with unclaimed rewards once it is within `RewardExpiryWarning` eras of
+        /// expiry.
         ///
-        /// # <weight>
-        /// - Independent of the arguments. Moderate complexity.
-        /// - O(1).
-        /// - Three DB entries.
-        /// # </weight>
-        #[pallet::weight(10_000)]
-        pub fn undelegate(
-            origin: OriginFor<T>,
-            validator: <T::Lookup as StaticLookup>::Source,
-            #[pallet::compact] amount: BalanceOf<T>,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-            let validator = T::Lookup::lookup(validator)?;
-            
-            // Check if is a delegator
-            ensure!(Delegators::<T>::contains_key(&who), Error::<T>::NotDelegator);
-            let mut delegator = Delegators::<T>::get(&who).ok_or(Error::<T>::NotDelegator)?;
+        /// The rewards of an era are claimable for `HistoryDepth` eras after it. Sweeping an era
+        /// removes its unclaimed rewards along with the rest of its era-indexed data: exposures,
+        /// validator list, totals and block counts. Swept rewards were never minted, so dropping
+        /// them leaves issuance unchanged. At most `MaxRewardsSweptPerEra` entries are removed per
+        /// rotation; `NextEraToSweep` records where the next rotation continues.
+        fn sweep_expired_rewards(new_era: EraIndex) {
+            let history_depth = T::HistoryDepth::get();
+            let first_kept = new_era.saturating_sub(history_depth);
+            
+            // Remove the eras that fell out of the history, within this rotation's budget
+            let mut next_to_sweep = Self::next_era_to_sweep();
+            let mut budget = T::MaxRewardsSweptPerEra::get();
+            while next_to_sweep < first_kept && !budget.is_zero() {
+                let era = next_to_sweep;
+                let clears: [fn(EraIndex, u32) -> (u32, bool); 5] = [
+                    |era, limit| {
+                        let result = ErasValidatorReward::<T>::clear_prefix(era, limit, None);
+                        (result.unique, result.maybe_cursor.is_none())
+                    },
+                    |era, limit| {
+                        let result = ClaimedRewards::<T>::clear_prefix(era, limit, None);
+                        (result.unique, result.maybe_cursor.is_none())
+                    },
+                    |era, limit| {
+                        let result = ErasRewardPoints::<T>::clear_prefix(era, limit, None);
+                        (result.unique, result.maybe_cursor.is_none())
+                    },
+                    |era, limit| {
+                        let result = ErasStakers::<T>::clear_prefix(era, limit, None);
+                        (result.unique, result.maybe_cursor.is_none())
+                    },
+                    |era, limit| {
+                        let result = ErasAuthoredBlocks::<T>::clear_prefix(era, limit, None);
+                        (result.unique, result.maybe_cursor.is_none())
+                    },
+                ];
+                
+                // The maps are cleared in order, and a map is only touched while budget remains
+                let mut cleared = true;
+                for clear in clears {
+                    if budget.is_zero() {
+                        cleared = false;
+                        break;
+                    }
+                    let (removed, done) = clear(era, budget);
+                    budget = budget.saturating_sub(removed);
+                    if !done {
+                        cleared = false;
+                        break;
+                    }
+                }
+                if !cleared {
+                    break;
+                }
+                
+                ErasTotalRewardPoints::<T>::remove(era);
+                ErasValidatorList::<T>::remove(era);
+                ErasTotalStake::<T>::remove(era);
+                ErasReward::<T>::remove(era);
+                ErasBlockCount::<T>::remove(era);
+                EraStartBlockNumber::<T>::remove(era);
+                ErasStartSessionIndex::<T>::remove(era);
+                next_to_sweep = next_to_sweep.saturating_add(1);
+                // Even an empty era costs a unit, bounding the number of eras visited
+                budget = budget.saturating_sub(1);
+            }
+            NextEraToSweep::<T>::put(next_to_sweep);
+            
+            // Expired eras no longer hold claimable rewards, even before they are removed
+            let mut earliest = Self::earliest_unclaimed_era().max(first_kept);
+            
+            // Skip the eras whose rewards have all been claimed. Only eras whose rewards have
+            // been computed count, since an uncomputed era has no entries yet
+            let first_uncomputed = new_era.saturating_sub(T::RewardPaymentDelay::get());
+            while earliest < first_uncomputed &&
+                !ErasValidatorReward::<T>::iter_key_prefix(earliest)
+                    .any(|validator| !ClaimedRewards::<T>::get(earliest, &validator))
+            {
+                earliest = earliest.saturating_add(1);
+            }
             
-            // Find the delegation
-            let delegation_idx = delegator.delegations.iter().position(|(v, _)| *v == validator)
-                .ok_or(Error::<T>::NotDelegator)?;
+            EarliestUnclaimedEra::<T>::put(earliest);
             
-            let current_delegation = delegator.delegations[delegation_idx].1;
-            ensure!(amount <= current_delegation, Error::<T>::InsufficientDelegationStake);
+            // An era is swept once `new_era` passes `earliest + history_depth`
+            let expires_at = earliest.saturating_add(history_depth).saturating_add(1);
+            if earliest < new_era &&
+                new_era.saturating_add(T::RewardExpiryWarning::get()) >= expires_at
+            {
+                Self::deposit_event(Event::RewardExpiringSoon(earliest));
+            }
+        }
+        
+        /// Pay the reward of `validator` for `era` to the validator and its delegators.
+        ///
+        /// The validator keeps its commission plus its own-stake share of the rest; each
+        /// delegator receives its stake share. Rounding dust goes to `DustRemoval`. Returns the
+        /// amount paid and the number of delegators paid.
+        fn do_payout_stakers(
+            validator: &T::AccountId,
+            era: EraIndex,
+            reward: EraValidatorReward<BalanceOf<T>>,
+        ) -> (BalanceOf<T>, u32) {
+            let exposure = ErasStakers::<T>::get(era, validator);
+            let mut total_allocated: BalanceOf<T> = Zero::zero();
+            let mut total_paid: BalanceOf<T> = Zero::zero();
+            let mut delegators_paid: u32 = 0;
+            
+            // Calculate validator's commission (percentage of rewards they keep)
+            let commission = reward.commission * reward.amount;
+            
+            // Calculate remaining reward to split by stake
+            let stakers_reward = reward.amount.saturating_sub(commission);
+            
+            // Reward the validator (their own stake + commission)
+            let validator_own_stake_ratio = Perbill::from_rational(exposure.own, exposure.total);
+            let validator_own_reward = validator_own_stake_ratio * stakers_reward;
+            let validator_total_reward = validator_own_reward.saturating_add(commission);
+            
+            if !validator_total_reward.is_zero() {
+                total_allocated = total_allocated.saturating_add(validator_total_reward);
+                let minted = Self::pay_reward(validator, validator, era, validator_total_reward);
+                total_paid = total_paid.saturating_add(minted);
+            }
             
-            // Check if validator exists
-            ensure!(Validators::<T>::contains_key(&validator), Error::<T>::NotValidator);
-            let mut validator_data = Validators::<T>::get(&validator).ok_or(Error::<T>::NotValidator)?;
+            // Pay each delegator its share
+            if !stakers_reward.is_zero() {
+                for delegation in exposure.delegations.iter() {
+                    let delegator_stake_ratio = Perbill::from_rational(delegation.value, exposure.total);
+                    let delegator_reward = delegator_stake_ratio * stakers_reward;
+                    
+                    if !delegator_reward.is_zero() {
+                        total_allocated = total_allocated.saturating_add(delegator_reward);
+                        let minted = Self::pay_reward(&delegation.who, validator, era, delegator_reward);
+                        total_paid = total_paid.saturating_add(minted);
+                        delegators_paid = delegators_paid.saturating_add(1);
+                    }
+                }
+            }
             
-            // Update validator's total stake
-            validator_data.total_stake = validator_data.total_stake.saturating_sub(amount);
-            Validators::<T>::insert(&validator, validator_data);
+            // Hand the rounding dust to the dust handler
+            Self::remove_dust(reward.amount.saturating_sub(total_allocated));
             
-            // Update delegator data
-            if amount == current_delegation {
-                // Remove delegation completely
-                delegator.delegations.remove(delegation_idx);
-            } else {
-                // Reduce delegation amount
-                delegator.delegations[delegation_idx].1 = current_delegation.saturating_sub(amount);
+            (total_paid, delegators_paid)
+        }
+        
+        /// Issue `dust` (up to the supply cap) and hand it to `DustRemoval`.
+        fn remove_dust(dust: BalanceOf<T>) {
+            if !dust.is_zero() {
+                let headroom = T::MaxTotalSupply::get().saturating_sub(T::Currency::total_issuance());
+                T::DustRemoval::on_unbalanced(T::Currency::issue(dust.min(headroom)));
+            }
+        }
+        
+        /// The full staking position of `who`: its validator stake, delegations and unbonding
+        /// chunks.
+        ///
+        /// Backs the `StakingAtlasApi::staking_overview` runtime API.
+        pub fn staking_overview(who: &T::AccountId) -> StakingOverview<T::AccountId, BalanceOf<T>> {
+            let validator = Validators::<T>::get(who);
+            let delegations = Delegators::<T>::get(who)
+                .map(|delegator| {
+                    delegator.delegations
+                        .into_iter()
+                        .map(|(validator, delegation)| (validator, delegation.amount))
+                        .collect()
+                })
+                .unwrap_or_default();
+            
+            StakingOverview {
+                is_validator: validator.is_some(),
+                self_stake: validator.as_ref().map(|v| v.self_stake).unwrap_or_else(Zero::zero),
+                total_backing: validator.as_ref().map(|v| v.total_stake).unwrap_or_else(Zero::zero),
+                reputation: validator.map(|v| v.reputation.score).unwrap_or_else(Zero::zero),
+                delegations,
+                unbonding_stake: UnbondingStake::<T>::get(who).into_inner(),
+                unbonding_delegations: UnbondingDelegations::<T>::get(who).into_inner(),
             }
+        }
+        
+        /// The reward `who` is still owed for `era`, as a validator and as a delegator, or `None`
+        /// if it has nothing to claim for that era.
+        ///
+        /// Mirrors the split of `do_payout_stakers` over every unclaimed validator reward of the
+        /// era without mutating state. Backs the `StakingAtlasApi::pending_reward` runtime API.
+        pub fn pending_reward(who: &T::AccountId, era: EraIndex) -> Option<BalanceOf<T>> {
+            let mut pending: Option<BalanceOf<T>> = None;
             
-            delegator.total_staked = delegator.total_staked.saturating_sub(amount);
+            for validator in ErasValidatorList::<T>::get(era).iter() {
+                if ClaimedRewards::<T>::get(era, validator) {
+                    continue;
+                }
+                let reward = match ErasValidatorReward::<T>::get(era, validator) {
+                    Some(reward) => reward,
+                    None => continue,
+                };
+                
+                let exposure = ErasStakers::<T>::get(era, validator);
+                let commission = reward.commission * reward.amount;
+                let stakers_reward = reward.amount.saturating_sub(commission);
+                
+                let share = if validator == who {
+                    Some(Perbill::from_rational(exposure.own, exposure.total) * stakers_reward + commission)
+                } else {
+                    exposure.delegations
+                        .iter()
+                        .find(|delegation| delegation.who == *who)
+                        .map(|delegation| Perbill::from_rational(delegation.value, exposure.total) * stakers_reward)
+                };
+                
+                if let Some(share) = share {
+                    pending = Some(pending.unwrap_or_else(Zero::zero).saturating_add(share));
+                }
+            }
             
-            if delegator.delegations.is_empty() {
-                // Remove delegator if no delegations left
-                Delegators::<T>::remove(&who);
-            } else {
-                // Update delegator
-                Delegators::<T>::insert(&who, delegator);
+            pending
+        }
+        
+        /// The weight of paying out a validator and `delegators` of its delegators.
+        pub fn payout_stakers_weight(delegators: u32) -> Weight {
+            T::WeightInfo::payout_stakers(delegators)
+        }
+        
+        /// Add the `EraReward` issuance to the reward of `era`.
+        ///
+        /// Nothing is minted here; the reward is minted when it is paid out, subject to
+        /// `MaxTotalSupply`.
+        fn add_issuance_to_reward(era: EraIndex) {
+            let issuance = T::EraReward::get();
+            if issuance.is_zero() {
+                return;
             }
             
-            // Note: We don't remove the lock on the tokens here.
-            // The tokens will be unlocked after the bonding period.
-            // For now, we'll just emit the event.
+            ErasReward::<T>::mutate(era, |reward| {
+                *reward = Some(reward.unwrap_or_else(Zero::zero).saturating_add(issuance));
+            });
+        }
+        
+        /// Move the fees accumulated in `RewardFeePot` into the reward of `era`.
+        fn add_fees_to_reward(era: EraIndex) {
+            let fees = RewardFeePot::<T>::take();
+            if fees.is_zero() {
+                return;
+            }
             
-            Self::deposit_event(Event::DelegationWithdrawn(who, validator, amount));
+            ErasReward::<T>::mutate(era, |reward| {
+                *reward = Some(reward.unwrap_or_else(Zero::zero).saturating_add(fees));
+            });
             
-            Ok(())
+            Self::deposit_event(Event::FeesAddedToReward(era, fees));
         }
         
-        /// Increase validator's self-stake.
+        /// The delegators backing `validator` together with their current delegation amounts.
         ///
-        /// The dispatch origin must be Signed and the account must be a registered validator.
+        /// Served from the `ValidatorNominators` reverse index, so the result is bounded by
+        /// `MaxNominatorsPerValidator`. Backs the `StakingAtlasApi::nominators_of` runtime API.
+        pub fn nominators_of(validator: &T::AccountId) -> Vec<(T::AccountId, BalanceOf<T>)> {
+            ValidatorNominators::<T>::get(validator)
+                .into_iter()
+                .filter_map(|nominator| {
+                    let amount = Delegators::<T>::get(&nominator)?
+                        .delegations
+                        .get(validator)?
+                        .amount;
+                    Some((nominator, amount))
+                })
+                .collect()
+        }
+        
+        /// The validator's self-stake as a fraction of its total stake.
         ///
-        /// # <weight>
-        /// - Independent of the arguments. Moderate complexity.
-        /// - O(1).
-        /// - Two DB entries.
-        /// # </weight>
-        #[pallet::weight(10_000)]
-        pub fn increase_stake(
-            origin: OriginFor<T>,
-            #[pallet::compact] additional_amount: BalanceOf<T>,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-            
-            // Check if is a validator
-            ensure!(Validators::<T>::contains_key(&who), Error::<T>::NotValidator);
-            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
-            
-            // Update validator's stake
-            validator.self_stake = validator.self_stake.saturating_add(additional_amount);
-            validator.total_stake = validator.total_stake.saturating_add(additional_amount);
-            
-            Validators::<T>::insert(&who, validator);
-            
-            // Lock additional tokens
-            T::Currency::set_lock(
-                LockIdentifier(*b"stakeatls"),
-                &who,
-                validator.self_stake,
-                WithdrawReasons::all(),
-            );
+        /// Returns `None` for non-validators and zero for a validator with no stake at all.
+        /// Backs the `StakingAtlasApi::self_bond_ratio` runtime API.
+        pub fn self_bond_ratio(who: &T::AccountId) -> Option<Perbill> {
+            let validator = Validators::<T>::get(who)?;
             
-            Self::deposit_event(Event::ValidatorStakeIncreased(who, additional_amount));
+            if validator.total_stake.is_zero() {
+                return Some(Perbill::zero());
+            }
             
-            Ok(())
+            Some(Perbill::from_rational(validator.self_stake, validator.total_stake))
         }
         
-        /// Decrease validator's self-stake.
-        ///
-        /// The dispatch origin must be Signed and the account must be a registered validator.
-        /// If the validator is active, they must deregister first.
+        /// The fraction of its expected blocks that `who` authored over the last `eras` completed
+        /// eras.
         ///
-        /// # <weight>
-        /// - Independent of the arguments. Moderate complexity.
-        /// - O(1).
-        /// - Two DB entries.
-        /// # </weight>
-        #[pallet::weight(10_000)]
-        pub fn decrease_stake(
-            origin: OriginFor<T>,
-            #[pallet::compact] amount: BalanceOf<T>,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+        /// A validator is expected to author an equal share of the blocks of every era it was
+        /// selected for; eras in which it was not selected are ignored. Returns zero when the
+        /// validator was not selected in any era of the window. Backs the
+        /// `StakingAtlasApi::uptime` runtime API.
+        pub fn uptime(who: &T::AccountId, eras: u32) -> Perbill {
+            let current_era = Self::current_era();
+            let mut authored: u32 = 0;
+            let mut expected: u32 = 0;
             
-            // Check if is a validator
-            ensure!(Validators::<T>::contains_key(&who), Error::<T>::NotValidator);
-            let mut validator = Validators::<T>::get(&who).ok_or(Error::<T>::NotValidator)?;
+            for era in current_era.saturating_sub(eras)..current_era {
+                if let Some((era_authored, era_expected)) = Self::era_block_share(era, who) {
+                    expected = expected.saturating_add(era_expected);
+                    authored = authored.saturating_add(era_authored.min(era_expected));
+                }
+            }
             
-            // Check if validator is active
-            ensure!(!validator.is_active, Error::<T>::CannotWithdrawWhileActive);
+            if expected.is_zero() {
+                return Perbill::zero();
+            }
             
-            // Check if there's enough stake to withdraw
-            ensure!(amount <= validator.self_stake, Error::<T>::InsufficientStake);
+            Perbill::from_rational(authored, expected)
+        }
+        
+        /// The blocks `who` authored in `era` and its expected share of the era's blocks, or
+        /// `None` if it was not selected for that era.
+        ///
+        /// Each selected validator is expected to author an equal share of the era's blocks.
+        fn era_block_share(era: EraIndex, who: &T::AccountId) -> Option<(u32, u32)> {
+            let validators = ErasValidatorList::<T>::get(era);
+            if !validators.contains(who) {
+                return None;
+            }
             
-            let min_stake = T::MinValidatorStake::get();
-            ensure!(
-                validator.self_stake.saturating_sub(amount) >= min_stake || 
-                validator.self_stake.saturating_sub(amount) == Zero::zero(),
-                Error::<T>::InsufficientStake
-            );
+            let expected = ErasBlockCount::<T>::get(era) / validators.len() as u32;
+            Some((ErasAuthoredBlocks::<T>::get(era, who), expected))
+        }
+        
+        /// The minimum self-stake currently required of a validator: the larger of the
+        /// configured `MinValidatorStake` and the governance-set `MinimumValidatorStake`.
+        pub fn effective_min_validator_stake() -> BalanceOf<T> {
+            T::MinValidatorStake::get().max(MinimumValidatorStake::<T>::get())
+        }
+        
+        /// The largest total stake a validator with `self_stake` may have: `MaxStakeMultiplier`
+        /// times its self-stake.
+        pub fn max_total_stake(self_stake: BalanceOf<T>) -> BalanceOf<T> {
+            self_stake.saturating_mul(T::MaxStakeMultiplier::get().into())
+        }
+        
+        /// Whether a validator's self-stake meets both the effective minimum and
+        /// `MinSelfStakeRatio` of its total stake.
+        fn has_sufficient_self_stake(
+            validator: &Validator<T::AccountId, BalanceOf<T>>,
+            min_stake: BalanceOf<T>,
+        ) -> bool {
+            validator.self_stake >= min_stake &&
+                validator.self_stake >= T::MinSelfStakeRatio::get() * validator.total_stake
+        }
+        
+        /// Move `Active` validators whose self-stake is below the effective minimum, or below
+        /// `MinSelfStakeRatio` of their total stake, to `InsufficientStake`, and restore those
+        /// that have since recovered.
+        fn enforce_minimum_stake() {
+            let min_stake = Self::effective_min_validator_stake();
             
-            // Update validator's stake
-            validator.self_stake = validator.self_stake.saturating_sub(amount);
-            validator.total_stake = validator.total_stake.saturating_sub(amount);
+            for (validator_id, validator_data) in Validators::<T>::iter() {
+                let sufficient = Self::has_sufficient_self_stake(&validator_data, min_stake);
+                match ValidatorStatuses::<T>::get(&validator_id) {
+                    ValidatorStatus::Active if !sufficient => {
+                        ValidatorStatuses::<T>::insert(&validator_id, ValidatorStatus::InsufficientStake);
+                    },
+                    ValidatorStatus::InsufficientStake if sufficient => {
+                        ValidatorStatuses::<T>::insert(&validator_id, ValidatorStatus::Active);
+                    },
+                    _ => {},
+                }
+            }
+        }
+        
+        /// The status a validator is effectively in, combining the stored base status with jail
+        /// and stake sufficiency.
+        ///
+        /// Jail takes precedence, followed by deregistration and then a self-stake below the
+        /// current minimum or `MinSelfStakeRatio` of the total stake. Backs the `StakingAtlasApi::effective_status` runtime API.
+        pub fn effective_status(who: &T::AccountId) -> ValidatorStatus {
+            let status = ValidatorStatuses::<T>::get(who);
+            let validator = match Validators::<T>::get(who) {
+                Some(validator) => validator,
+                None => return status,
+            };
             
-            // If validator has withdrawn all stake, remove them
-            if validator.self_stake.is_zero() {
-                Validators::<T>::remove(&who);
-                ValidatorStatuses::<T>::remove(&who);
-            } else {
-                Validators::<T>::insert(&who, validator);
+            if Self::is_jailed(who) {
+                return ValidatorStatus::Jailed;
             }
             
-            // Update lock
-            if !validator.self_stake.is_zero() {
-                T::Currency::set_lock(
+            match status {
+                ValidatorStatus::Active
+                    if !Self::has_sufficient_self_stake(&validator, Self::effective_min_validator_stake()) =>
+                {
+                    ValidatorStatus::InsufficientStake
+                },
+                status => status,
+            }
+        }
+        
+        /// Set the `stakeatls` lock of `who` to its bonded self-stake plus its unbonding chunks,
+        /// removing the lock when both are zero.
+        ///
+        /// Every change to a validator's self-stake or unbonding chunks must end here rather
+        /// than calling `set_lock` directly, so the lock always covers exactly what is staked.
+        fn update_stake_lock(who: &T::AccountId) {
+            let self_stake = Validators::<T>::get(who)
+                .map(|validator| validator.self_stake)
+                .unwrap_or_else(Zero::zero);
+            let unbonding = Self::chunks_total(&UnbondingStake::<T>::get(who));
+            let locked = self_stake.saturating_add(unbonding);
+            
+            if locked.is_zero() {
+                T::Currency::remove_lock(
                     LockIdentifier(*b"stakeatls"),
-                    &who,
-                    validator.self_stake,
-                    WithdrawReasons::all(),
+                    who,
                 );
             } else {
-                T::Currency::remove_lock(
+                T::Currency::set_lock(
                     LockIdentifier(*b"stakeatls"),
-                    &who,
+                    who,
+                    locked,
+                    WithdrawReasons::all(),
                 );
             }
-            
-            Self::deposit_event(Event::ValidatorStakeDecreased(who, amount));
-            
-            Ok(())
         }
         
-        /// Calculate and distribute rewards for an era.
-        fn distribute_rewards(era: EraIndex) -> DispatchResult {
-            // Check if rewards for this era are available
-            let era_reward = ErasReward::<T>::get(era).ok_or(Error::<T>::NoRewardsForEra)?;
-            
-            // Get validators for this era
-            let validators = ErasValidatorList::<T>::get(era);
-            
-            // If no validators, return early
-            if validators.is_empty() {
-                return Ok(());
-            }
-            
-            // Get total stake for this era
-            let total_stake = ErasTotalStake::<T>::get(era);
-            
-            // If total stake is zero, return early
-            if total_stake.is_zero() {
-                return Ok(());
+        /// Slash the validator of `slash` and the delegations it records.
+        ///
+        /// Each share is taken from the stake that is still bonded first and then from the
+        /// account's unbonding chunks, newest first, so unbonding after the report does not
+        /// escape the slash. A validator that has left storage since is still slashed on its
+        /// unbonding chunks.
+        fn apply_slash(slash: UnappliedSlashOf<T>) {
+            let UnappliedSlash { validator, proportion, own, delegators } = slash;
+            let mut maybe_validator = Validators::<T>::get(&validator);
+            
+            // Slash the validator's own stake
+            let bonded_own = maybe_validator.as_ref().map_or_else(Zero::zero, |v| v.self_stake);
+            let unbonding_own = Self::chunks_total(&UnbondingStake::<T>::get(&validator));
+            let own_slash = (proportion * own).min(bonded_own.saturating_add(unbonding_own));
+            let (mut slashed_imbalance, _) = T::Currency::slash(&validator, own_slash);
+            
+            // The stake only shrinks by what was actually taken
+            let own_slashed = slashed_imbalance.peek();
+            let mut total_slashed = own_slashed;
+            
+            let from_bonded = own_slashed.min(bonded_own);
+            if let Some(validator_data) = maybe_validator.as_mut() {
+                validator_data.self_stake = validator_data.self_stake.saturating_sub(from_bonded);
+                validator_data.total_stake = validator_data.total_stake.saturating_sub(from_bonded);
             }
-            
-            let mut reward_remainder = era_reward;
-            
-            // For each validator
-            for validator_id in validators.iter() {
-                // Get validator exposure
-                let exposure = ErasStakers::<T>::get(era, validator_id);
-                
-                // Calculate validator's share of rewards based on stake
-                let validator_stake_ratio = Perbill::from_rational(exposure.total, total_stake);
-                let validator_reward = validator_stake_ratio * era_reward;
-                
-                // If validator reward is zero, skip
-                if validator_reward.is_zero() {
+            UnbondingStake::<T>::mutate_exists(&validator, |chunks| {
+                Self::slash_chunks(chunks, own_slashed.saturating_sub(from_bonded))
+            });
+            
+            // Slash every recorded delegation by the same proportion
+            for (nominator, value) in delegators {
+                let delegated = Delegators::<T>::get(&nominator)
+                    .and_then(|delegator| delegator.delegations.get(&validator).map(|d| d.amount))
+                    .unwrap_or_else(Zero::zero);
+                let unbonding = Self::chunks_total(&UnbondingDelegations::<T>::get(&nominator));
+                let delegation_slash = (proportion * value).min(delegated.saturating_add(unbonding));
+                if delegation_slash.is_zero() {
                     continue;
                 }
                 
-                // Get the reputation adjustment for rewards
-                // Higher reputation means higher rewards
-                let reputation = match Validators::<T>::get(validator_id) {
-                    Some(v) => v.reputation.score,
-                    None => Zero::zero(),
-                };
-                
-                // We'll use a simple linear reputation adjustment for now
-                // A more sophisticated model could be implemented
-                let reputation_factor = Perbill::from_rational(reputation, 100u32.into());
-                let reputation_bonus = Perbill::from_percent(10) * reputation_factor * validator_reward;
-                let adjusted_validator_reward = validator_reward.saturating_add(reputation_bonus);
-                
-                // Ensure we don't exceed total reward
-                let actual_validator_reward = if adjusted_validator_reward > reward_remainder {
-                    reward_remainder
-                } else {
-                    adjusted_validator_reward
-                };
-                
-                reward_remainder = reward_remainder.saturating_sub(actual_validator_reward);
-                
-                // Calculate validator's commission (percentage of rewards they keep)
-                // For simplicity, let's use a fixed 10% commission
-                let commission_rate = Perbill::from_percent(10);
-                let commission = commission_rate * actual_validator_reward;
-                
-                // Calculate remaining reward to distribute to delegators
-                let delegators_reward = actual_validator_reward.saturating_sub(commission);
+                let (imbalance, _) =
+                    T::Currency::slash_reserved_named(&DELEGATION_RESERVE_ID, &nominator, delegation_slash);
+                let slashed = imbalance.peek();
                 
-                // Reward the validator (their own stake + commission)
-                let validator_own_stake_ratio = Perbill::from_rational(exposure.own, exposure.total);
-                let validator_own_reward = validator_own_stake_ratio * delegators_reward;
-                let validator_total_reward = validator_own_reward.saturating_add(commission);
-                
-                // Send reward to validator
-                if !validator_total_reward.is_zero() {
-                    let _ = T::Currency::deposit_creating(validator_id, validator_total_reward);
-                }
-                
-                // Distribute remaining reward to delegators
-                if !delegators_reward.is_zero() && !exposure.delegations.is_empty() {
-                    let remaining_delegators_reward = delegators_reward.saturating_sub(validator_own_reward);
-                    
-                    for delegation in exposure.delegations.iter() {
-                        let delegator_stake_ratio = Perbill::from_rational(delegation.value, exposure.total);
-                        let delegator_reward = delegator_stake_ratio * delegators_reward;
-                        
-                        if !delegator_reward.is_zero() {
-                            let _ = T::Currency::deposit_creating(&delegation.who, delegator_reward);
+                let from_delegation = slashed.min(delegated);
+                if !from_delegation.is_zero() {
+                    Delegators::<T>::mutate(&nominator, |maybe_delegator| {
+                        if let Some(delegator) = maybe_delegator {
+                            if let Some(delegation) = delegator.delegations.get_mut(&validator) {
+                                delegation.reduce(from_delegation);
+                            }
+                            delegator.total_staked = delegator.total_staked.saturating_sub(from_delegation);
                         }
+                    });
+                    if let Some(validator_data) = maybe_validator.as_mut() {
+                        validator_data.total_stake = validator_data.total_stake.saturating_sub(from_delegation);
                     }
                 }
+                UnbondingDelegations::<T>::mutate_exists(&nominator, |chunks| {
+                    Self::slash_chunks(chunks, slashed.saturating_sub(from_delegation))
+                });
+                
+                total_slashed = total_slashed.saturating_add(slashed);
+                slashed_imbalance.subsume(imbalance);
             }
             
-            // Emit event
-            Self::deposit_event(Event::RewardsPaid(era, era_reward.saturating_sub(reward_remainder)));
+            // Take the validator out of selection
+            if let Some(mut validator_data) = maybe_validator {
+                validator_data.is_active = false;
+                Validators::<T>::insert(&validator, validator_data);
+                ValidatorStatuses::<T>::insert(&validator, ValidatorStatus::Slashed);
+                Self::jail_validator(&validator);
+            }
+            Self::update_stake_lock(&validator);
             
-            // Remove era reward after distribution
-            ErasReward::<T>::remove(era);
+            T::Slash::on_unbalanced(slashed_imbalance);
             
-            Ok(())
+            Self::deposit_event(Event::ValidatorSlashed(validator, total_slashed));
         }
-    }
-
-    #[pallet::hooks]
-    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-        fn on_initialize(n: T::BlockNumber) -> Weight {
-            // Calculate expected era block
-            let current_era = Self::current_era();
-            let era_start_block = Self::era_start_block_number(current_era);
-            let era_duration = T::EraDuration::get();
-            let expected_era_end = era_start_block.saturating_add(era_duration);
-            
-            // Check if we need to start a new era
-            if n >= expected_era_end {
-                // Start new era
-                let new_era = current_era.saturating_add(1);
-                CurrentEra::<T>::put(new_era);
-                EraStartBlockNumber::<T>::insert(new_era, n);
-                
-                // Update validator reputation scores
-                Self::update_reputation_scores();
-                
-                // Select validators for the new era
-                let _validators = Self::select_validators();
-                
-                // Update validator set for the next session
-                Self::update_validator_set();
-                
-                // Distribute rewards for the previous era (with a delay)
-                if current_era >= T::RewardPaymentDelay::get() {
-                    let reward_era = current_era.saturating_sub(T::RewardPaymentDelay::get());
-                    let _ = Self::distribute_rewards(reward_era);
+        
+        /// The total amount held in unbonding `chunks`.
+        pub(crate) fn chunks_total(chunks: &[(EraIndex, BalanceOf<T>)]) -> BalanceOf<T> {
+            chunks.iter().fold(Zero::zero(), |total: BalanceOf<T>, (_, amount)| total.saturating_add(*amount))
+        }
+        
+        /// Remove up to `amount` from unbonding `chunks`, newest first, dropping emptied chunks.
+        pub(crate) fn slash_chunks(
+            maybe_chunks: &mut Option<BoundedVec<(EraIndex, BalanceOf<T>), T::MaxUnlockingChunks>>,
+            mut amount: BalanceOf<T>,
+        ) {
+            if let Some(chunks) = maybe_chunks {
+                while !amount.is_zero() {
+                    let (unlock_era, chunk) = match chunks.pop() {
+                        Some(chunk) => chunk,
+                        None => break,
+                    };
+                    if chunk > amount {
+                        // Cannot fail: a chunk was just popped
+                        let _ = chunks.try_push((unlock_era, chunk.saturating_sub(amount)));
+                        break;
+                    }
+                    amount = amount.saturating_sub(chunk);
+                }
+                if chunks.is_empty() {
+                    *maybe_chunks = None;
                 }
-                
-                Self::deposit_event(Event::NewEra(new_era));
-                
-                // Return weight indicating moderate computation
-                return Weight::from_parts(50_000_000, 0);
             }
-            
-            // No era change, return minimal weight
-            Weight::from_parts(5_000_000, 0)
         }
         
-        fn on_finalize(_n: T::BlockNumber) {
-            // No finalization logic needed for now
+        /// Whether a deferred slash affecting `who`, as the validator or as one of its recorded
+        /// delegators, is still waiting to be applied.
+        fn has_pending_slash(who: &T::AccountId) -> bool {
+            UnappliedSlashes::<T>::iter_values().any(|slashes| {
+                slashes.iter().any(|slash| {
+                    slash.validator == *who || slash.delegators.iter().any(|(nominator, _)| nominator == who)
+                })
+            })
         }
-    }
-
-    // Additional implementation for the pallet
-    impl<T: Config> Pallet<T> {
-        /// Select validators for the next era based on stake and reputation.
-        fn select_validators() -> Vec<T::AccountId> {
-            // Get all active validators
-            let mut validators: Vec<(T::AccountId, BalanceOf<T>, BalanceOf<T>)> = Vec::new();
+        
+        /// Apply the slashes deferred to `era`.
+        fn apply_unapplied_slashes(era: EraIndex) {
+            for slash in UnappliedSlashes::<T>::take(era) {
+                Self::apply_slash(slash);
+            }
+        }
+        
+        /// Delegate `amount` from `who` to `validator`, reserving it from `who`'s free balance.
+        fn do_delegate(
+            who: &T::AccountId,
+            validator: T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            // Check if validator exists, has bonded stake and is active
+            ensure!(!Self::is_declared(&validator), Error::<T>::NotBonded);
+            ensure!(Validators::<T>::contains_key(&validator), Error::<T>::NotValidator);
+            let mut validator_data = Validators::<T>::get(&validator).ok_or(Error::<T>::NotValidator)?;
+            // `ValidatorStatuses` is authoritative: `is_active` can lag behind a slash or a
+            // stake shortfall, and such a validator will never be selected
+            ensure!(
+                validator_data.is_active && Self::validator_status(&validator) == ValidatorStatus::Active,
+                Error::<T>::ValidatorNotActive
+            );
             
-            for (validator_id, validator_data) in Validators::<T>::iter() {
-                // Only consider active validators
-                if validator_data.is_active {
-                    let status = ValidatorStatuses::<T>::get(&validator_id);
-                    if status == ValidatorStatus::Active {
-                        // Calculate validator score as a combination of stake and reputation
-                        // Formula: score = (1 - reputation_weight) * stake + reputation_weight * reputation
-                        let reputation_weight = T::ReputationWeight::get();
-                        let stake_weight = Perbill::from_percent(100) - reputation_weight;
-                        
-                        let stake_score = stake_weight * validator_data.total_stake;
-                        let reputation_score = reputation_weight * validator_data.reputation.score;
-                        
-                        let total_score = stake_score + reputation_score;
-                        
-                        validators.push((validator_id, total_score, validator_data.total_stake));
+            // Check minimum delegation stake, including the validator's own minimum
+            let min_delegation = match Self::validator_prefs(&validator).min_delegation {
+                Some(validator_min) => T::MinDelegationStake::get().max(validator_min),
+                None => T::MinDelegationStake::get(),
+            };
+            ensure!(amount >= min_delegation, Error::<T>::InsufficientDelegationStake);
+            
+            // Cap the validator's leverage on its own stake
+            ensure!(
+                validator_data.total_stake.saturating_add(amount) <=
+                    Self::max_total_stake(validator_data.self_stake),
+                Error::<T>::SelfBondTooLow
+            );
+            
+            // A delegation or top-up made mid-era only earns from the next era onwards
+            let current_era = CurrentEra::<T>::get();
+            let new_delegation = Delegation {
+                amount,
+                since_era: current_era.saturating_add(1),
+                compound: false,
+                pending: None,
+            };
+            
+            // Update or create delegator
+            match Delegators::<T>::get(who) {
+                Some(mut delegator) => {
+                    if let Some(delegation) = delegator.delegations.get_mut(&validator) {
+                        // Top up the existing delegation, keeping its `since_era` and `compound`
+                        delegation.top_up(amount, current_era);
+                    } else {
+                        // Add new delegation, bounded by the maximum delegations
+                        delegator.delegations.try_insert(validator.clone(), new_delegation)
+                            .map_err(|_| Error::<T>::TooManyDelegations)?;
                     }
-                }
+                    delegator.total_staked = delegator.total_staked.saturating_add(amount);
+                    
+                    Delegators::<T>::insert(who, delegator);
+                },
+                None => {
+                    // Create new delegator
+                    let mut delegations = BoundedBTreeMap::new();
+                    delegations.try_insert(validator.clone(), new_delegation)
+                        .map_err(|_| Error::<T>::TooManyDelegations)?;
+                    
+                    let delegator = Delegator {
+                        account: who.clone(),
+                        delegations,
+                        total_staked: amount,
+                    };
+                    
+                    Delegators::<T>::insert(who, delegator);
+                },
             }
             
-            // Sort validators by total score (descending)
-            validators.sort_by(|a, b| b.1.cmp(&a.1));
-            
-            // Select top N validators where N is ValidatorsCount
-            let count = T::ValidatorsCount::get() as usize;
-            let selected = validators.into_iter()
-                .take(count)
-                .map(|(id, _, _)| id)
-                .collect::<Vec<_>>();
+            // Track the delegator in the validator's nominator list
+            let nominators = ValidatorNominators::<T>::get(&validator);
+            if !nominators.contains(who) {
+                // Under `Reject` a full validator stays closed to new delegators until one leaves
+                if T::OversubscriptionPolicy::get() == OversubscriptionPolicy::Reject {
+                    ensure!(validator_data.delegations_open, Error::<T>::DelegationsClosed);
+                }
+                
+                // Make room for the new delegator if the validator is oversubscribed
+                if nominators.len() as u32 >= T::MaxNominatorsPerValidator::get() {
+                    match T::OversubscriptionPolicy::get() {
+                        OversubscriptionPolicy::Reject => return Err(Error::<T>::TooManyDelegators.into()),
+                        OversubscriptionPolicy::EvictSmallest => {
+                            Self::evict_smallest_delegation(&validator, &mut validator_data, amount)?;
+                        },
+                    }
+                }
+                
+                let nominator_count = ValidatorNominators::<T>::try_mutate(&validator, |nominators| {
+                    nominators.try_push(who.clone()).map(|_| nominators.len() as u32)
+                })
+                .map_err(|_| Error::<T>::TooManyDelegators)?;
+                
+                // Close the validator to new delegators once the cap is reached
+                if nominator_count >= T::MaxNominatorsPerValidator::get() {
+                    validator_data.delegations_open = false;
+                    Self::deposit_event(Event::ValidatorFull(validator.clone()));
+                }
+            }
             
-            // Store selected validators for the current era
-            let current_era = Self::current_era();
-            ErasValidatorList::<T>::insert(current_era, selected.clone());
+            // Reserve only this delegation; earlier delegations are already reserved
+            T::Currency::reserve_named(&DELEGATION_RESERVE_ID, who, amount)
+                .map_err(|_| Error::<T>::InsufficientBalance)?;
             
-            // Calculate total stake of selected validators
-            let total_stake = validators.iter()
-                .filter(|(id, _, _)| selected.contains(id))
-                .fold(Zero::zero(), |acc, (_, _, stake)| acc + *stake);
+            // Update validator's total stake
+            validator_data.total_stake = validator_data.total_stake.saturating_add(amount);
+            Validators::<T>::insert(&validator, validator_data);
             
-            ErasTotalStake::<T>::insert(current_era, total_stake);
+            Self::deposit_event(Event::DelegationCreated(who.clone(), validator, amount));
             
-            selected
+            Ok(())
         }
         
-        /// Calculate and distribute rewards for an era.
-        fn distribute_rewards(era: EraIndex) -> DispatchResult {
-            // Check if rewards for this era are available
-            let era_reward = ErasReward::<T>::get(era).ok_or(Error::<T>::NoRewardsForEra)?;
-            
-            // Get validators for this era
-            let validators = ErasValidatorList::<T>::get(era);
-            
-            // If no validators, return early
-            if validators.is_empty() {
-                return Ok(());
+        /// Add reward points to validators in the current era.
+        ///
+        /// The era reward is split between validators by their share of the era's points.
+        pub fn reward_by_ids(validators_points: impl IntoIterator<Item = (T::AccountId, RewardPoint)>) {
+            let era = Self::current_era();
+            for (validator, points) in validators_points {
+                ErasRewardPoints::<T>::mutate(era, &validator, |total| *total = total.saturating_add(points));
+                ErasTotalRewardPoints::<T>::mutate(era, |total| *total = total.saturating_add(points));
             }
+        }
+        
+        /// Whether `who` has declared its intent to validate without bonding stake yet.
+        pub fn is_declared(who: &T::AccountId) -> bool {
+            ValidatorStatuses::<T>::get(who) == ValidatorStatus::Declared
+        }
+        
+        /// Whether `who` is currently jailed.
+        pub fn is_jailed(who: &T::AccountId) -> bool {
+            Jailed::<T>::contains_key(who)
+        }
+        
+        /// Jail `who` until `current_era + JailDuration`, excluding it from selection until it
+        /// calls `unjail`. Called from the slashing path after an offence.
+        pub fn jail_validator(who: &T::AccountId) {
+            let release_era = Self::current_era().saturating_add(T::JailDuration::get());
+            Jailed::<T>::insert(who, release_era);
             
-            // Get total stake for this era
-            let total_stake = ErasTotalStake::<T>::get(era);
+            Self::deposit_event(Event::ValidatorJailed(who.clone(), release_era));
+        }
+        
+        /// Where the rewards of `who` are paid, defaulting to `RewardDestination::Stash`.
+        ///
+        /// Backs the `StakingAtlasApi::reward_destination` runtime API.
+        pub fn reward_destination(who: &T::AccountId) -> RewardDestination<T::AccountId> {
+            Payee::<T>::get(who).unwrap_or_default()
+        }
+        
+        /// The concrete account that receives the rewards of `who`.
+        fn payee_account(who: &T::AccountId) -> T::AccountId {
+            match Self::reward_destination(who) {
+                RewardDestination::Stash | RewardDestination::Compound => who.clone(),
+                RewardDestination::Account(account) => account,
+            }
+        }
+        
+        /// Pay `amount` of era `era` rewards, earned by backing `validator`, to `who`.
+        ///
+        /// The payee of `who` is resolved independently of the validator's, so a compounding
+        /// validator does not compound its delegators' rewards and vice versa. A delegation with
+        /// `compound` set is compounded whatever the delegator's payee, up to the validator's
+        /// `max_total_stake`, as a top-up pending until the next era; the excess goes to the
+        /// payee.
+        ///
+        /// A failed deposit, or a compounding excess that cannot be transferred to the payee, is
+        /// recorded in `FailedPayouts` and announced with `PayoutFailed` so that it can be paid
+        /// later through `retry_payout`. Returns the amount paid.
+        fn pay_reward(
+            who: &T::AccountId,
+            validator: &T::AccountId,
+            era: EraIndex,
+            amount: BalanceOf<T>,
+        ) -> BalanceOf<T> {
+            let compound = Self::reward_destination(who) == RewardDestination::Compound ||
+                (who != validator && Self::delegation_compounds(who, validator));
+            let payee = if compound { who.clone() } else { Self::payee_account(who) };
+            
+            match Self::mint_capped(&payee, amount) {
+                Ok(minted) => {
+                    if !compound {
+                        return minted;
+                    }
+                    
+                    // Whatever cannot be compounded is paid out as it would be otherwise
+                    let excess = Self::compound_reward(who, validator, minted);
+                    let cash_payee = Self::payee_account(who);
+                    if excess.is_zero() ||
+                        cash_payee == *who ||
+                        T::Currency::transfer(who, &cash_payee, excess, ExistenceRequirement::KeepAlive).is_ok()
+                    {
+                        return minted;
+                    }
+                    
+                    // Burn the undeliverable excess again so that `retry_payout` pays it once
+                    let (_, unslashed) = T::Currency::slash(who, excess);
+                    let owed = excess.saturating_sub(unslashed);
+                    FailedPayouts::<T>::mutate(era, who, |total| *total = total.saturating_add(owed));
+                    Self::deposit_event(Event::PayoutFailed(who.clone(), era, owed));
+                    minted.saturating_sub(owed)
+                },
+                Err(_) => {
+                    FailedPayouts::<T>::mutate(era, who, |owed| *owed = owed.saturating_add(amount));
+                    Self::deposit_event(Event::PayoutFailed(who.clone(), era, amount));
+                    Zero::zero()
+                }
+            }
+        }
+        
+        /// Whether the delegation of `who` to `validator` has `compound` set.
+        fn delegation_compounds(who: &T::AccountId, validator: &T::AccountId) -> bool {
+            Delegators::<T>::get(who)
+                .and_then(|delegator| delegator.delegations.get(validator).map(|delegation| delegation.compound))
+                .unwrap_or(false)
+        }
+        
+        /// Add a freshly paid reward of `who` to the stake it was earned with: the self-stake if
+        /// `who` is the validator, otherwise the delegation of `who` to `validator`.
+        ///
+        /// A delegation only grows up to the validator's `max_total_stake`. Returns the part of
+        /// `amount` left liquid in the account: the excess over that cap, or all of it if the
+        /// stake no longer exists or the reward cannot be reserved.
+        fn compound_reward(who: &T::AccountId, validator: &T::AccountId, amount: BalanceOf<T>) -> BalanceOf<T> {
+            if amount.is_zero() {
+                return Zero::zero();
+            }
             
-            // If total stake is zero, return early
-            if total_stake.is_zero() {
-                return Ok(());
+            if who == validator {
+                let compounded = Validators::<T>::mutate(who, |maybe_validator| {
+                    let validator_data = match maybe_validator {
+                        Some(validator_data) => validator_data,
+                        None => return false,
+                    };
+                    validator_data.self_stake = validator_data.self_stake.saturating_add(amount);
+                    validator_data.total_stake = validator_data.total_stake.saturating_add(amount);
+                    true
+                });
+                if !compounded {
+                    return amount;
+                }
+                Self::update_stake_lock(who);
+                return Zero::zero();
             }
             
-            let mut reward_remainder = era_reward;
+            let headroom = match Validators::<T>::get(validator) {
+                Some(validator_data) =>
+                    Self::max_total_stake(validator_data.self_stake).saturating_sub(validator_data.total_stake),
+                None => return amount,
+            };
+            let excess = amount.saturating_sub(headroom);
+            let compounding = amount.saturating_sub(excess);
+            if compounding.is_zero() {
+                return excess;
+            }
             
-            // For each validator
-            for validator_id in validators.iter() {
-                // Get validator exposure
-                let exposure = ErasStakers::<T>::get(era, validator_id);
-                
-                // Calculate validator's share of rewards based on stake
-                let validator_stake_ratio = Perbill::from_rational(exposure.total, total_stake);
-                let validator_reward = validator_stake_ratio * era_reward;
-                
-                // If validator reward is zero, skip
-                if validator_reward.is_zero() {
-                    continue;
-                }
-                
-                // Get the reputation adjustment for rewards
-                // Higher reputation means higher rewards
-                let reputation = match Validators::<T>::get(validator_id) {
-                    Some(v) => v.reputation.score,
-                    None => Zero::zero(),
+            let compounded = Delegators::<T>::mutate(who, |maybe_delegator| {
+                let delegator = match maybe_delegator {
+                    Some(delegator) => delegator,
+                    None => return false,
                 };
-                
-                // We'll use a simple linear reputation adjustment for now
-                // A more sophisticated model could be implemented
-                let reputation_factor = Perbill::from_rational(reputation, 100u32.into());
-                let reputation_bonus = Perbill::from_percent(10) * reputation_factor * validator_reward;
-                let adjusted_validator_reward = validator_reward.saturating_add(reputation_bonus);
-                
-                // Ensure we don't exceed total reward
-                let actual_validator_reward = if adjusted_validator_reward > reward_remainder {
-                    reward_remainder
-                } else {
-                    adjusted_validator_reward
+                let delegation = match delegator.delegations.get_mut(validator) {
+                    Some(delegation) => delegation,
+                    None => return false,
                 };
-                
-                reward_remainder = reward_remainder.saturating_sub(actual_validator_reward);
-                
-                // Calculate validator's commission (percentage of rewards they keep)
-                // For simplicity, let's use a fixed 10% commission
-                let commission_rate = Perbill::from_percent(10);
-                let commission = commission_rate * actual_validator_reward;
-                
-                // Calculate remaining reward to distribute to delegators
-                let delegators_reward = actual_validator_reward.saturating_sub(commission);
-                
-                // Reward the validator (their own stake + commission)
-                let validator_own_stake_ratio = Perbill::from_rational(exposure.own, exposure.total);
-                let validator_own_reward = validator_own_stake_ratio * delegators_reward;
-                let validator_total_reward = validator_own_reward.saturating_add(commission);
-                
-                // Send reward to validator
-                if !validator_total_reward.is_zero() {
-                    let _ = T::Currency::deposit_creating(validator_id, validator_total_reward);
+                if T::Currency::reserve_named(&DELEGATION_RESERVE_ID, who, compounding).is_err() {
+                    return false;
                 }
                 
-                // Distribute remaining reward to delegators
-                if !delegators_reward.is_zero() && !exposure.delegations.is_empty() {
-                    let remaining_delegators_reward = delegators_reward.saturating_sub(validator_own_reward);
-                    
-                    for delegation in exposure.delegations.iter() {
-                        let delegator_stake_ratio = Perbill::from_rational(delegation.value, exposure.total);
-                        let delegator_reward = delegator_stake_ratio * delegators_reward;
-                        
-                        if !delegator_reward.is_zero() {
-                            let _ = T::Currency::deposit_creating(&delegation.who, delegator_reward);
-                        }
-                    }
+                // Like any other top-up, the reward only counts from the next era
+                delegation.top_up(compounding, Self::current_era());
+                delegator.total_staked = delegator.total_staked.saturating_add(compounding);
+                true
+            });
+            
+            if !compounded {
+                return amount;
+            }
+            Validators::<T>::mutate(validator, |maybe_validator| {
+                if let Some(validator_data) = maybe_validator {
+                    validator_data.total_stake = validator_data.total_stake.saturating_add(compounding);
                 }
+            });
+            excess
+        }
+        
+        /// Mint `amount` into `who` without letting total issuance exceed `MaxTotalSupply`.
+        ///
+        /// Near the ceiling the minted amount is reduced to the remaining headroom and
+        /// `SupplyCapReached` is emitted. Returns the amount actually minted, or `DepositFailed`
+        /// if the account could not receive it (e.g. a new account below the existential deposit).
+        fn mint_capped(who: &T::AccountId, amount: BalanceOf<T>) -> Result<BalanceOf<T>, DispatchError> {
+            let headroom = T::MaxTotalSupply::get().saturating_sub(T::Currency::total_issuance());
+            let capped = amount.min(headroom);
+            
+            if capped < amount {
+                Self::deposit_event(Event::SupplyCapReached(amount.saturating_sub(capped)));
             }
             
-            // Emit event
-            Self::deposit_event(Event::RewardsPaid(era, era_reward.saturating_sub(reward_remainder)));
+            if capped.is_zero() {
+                return Ok(Zero::zero());
+            }
             
-            // Remove era reward after distribution
-            ErasReward::<T>::remove(era);
+            let minted = T::Currency::deposit_creating(who, capped).peek();
+            ensure!(minted == capped, Error::<T>::DepositFailed);
             
-            Ok(())
+            Ok(minted)
         }
         
-        /// Calculate a validator's reputation score based on performance.
-        fn calculate_reputation_score(validator: &T::AccountId) -> BalanceOf<T> {
-            // TODO: Implement a more sophisticated reputation score calculation
-            // For now, just return the current reputation score if it exists
-            if let Some(validator_data) = Validators::<T>::get(validator) {
-                return validator_data.reputation.score;
-            }
+        /// Calculate a validator's reputation score from its block production in `era`.
+        ///
+        /// The era's performance is the percentage of its expected share of blocks the validator
+        /// authored; authoring exactly the expected share scores 100. The current score is
+        /// multiplied by `ReputationDecay` and the remaining weight goes to the era's performance,
+        /// so validators that author nothing, or are not selected at all, trend toward zero.
+        /// The result is clamped to `MaxReputationScore`. Validators selected for an era without
+        /// any recorded blocks keep their current score.
+        fn calculate_reputation_score(validator: &T::AccountId, era: EraIndex) -> BalanceOf<T> {
+            let current_score = Validators::<T>::get(validator)
+                .map(|validator_data| validator_data.reputation.score)
+                .unwrap_or_else(Zero::zero);
+            
+            let performance = match Self::era_block_share(era, validator) {
+                Some((_, expected)) if expected.is_zero() => return current_score,
+                Some((authored, expected)) => BalanceOf::<T>::from(authored)
+                    .saturating_mul(100u32.into())
+                    / BalanceOf::<T>::from(expected),
+                // Validators left out of the era performed nothing, so their score only decays
+                None => Zero::zero(),
+            };
+            
+            let decay = T::ReputationDecay::get();
+            let score = (decay * current_score)
+                .saturating_add(decay.left_from_one() * performance.min(T::MaxReputationScore::get()));
             
-            Zero::zero()
+            score.min(T::MaxReputationScore::get())
         }
         
         /// Update validator reputation scores based on their performance.
         fn update_reputation_scores() {
+            // Scores are computed at rotation from the era that just ended
+            let ended_era = Self::current_era().saturating_sub(1);
+            
             for (validator_id, mut validator_data) in Validators::<T>::iter() {
                 // Calculate new reputation score
-                let new_score = Self::calculate_reputation_score(&validator_id);
+                let new_score = Self::calculate_reputation_score(&validator_id, ended_era);
                 
                 // Update reputation score
                 validator_data.reputation.score = new_score;
@@ -1013,14 +3816,88 @@ pub mod pallet {
                 Self::deposit_event(Event::ReputationUpdated(validator_id, new_score));
             }
         }
+    }
+}
+
+/// Records block authorship per era, feeding `Pallet::uptime`, and awards the author
+/// `BLOCK_AUTHORING_POINTS`.
+impl<T: Config> pallet_authorship::EventHandler<T::AccountId, T::BlockNumber> for Pallet<T> {
+    fn note_author(author: T::AccountId) {
+        let era = Pallet::<T>::current_era();
+        ErasAuthoredBlocks::<T>::mutate(era, &author, |blocks| *blocks = blocks.saturating_add(1));
+        ErasBlockCount::<T>::mutate(era, |blocks| *blocks = blocks.saturating_add(1));
+        Pallet::<T>::reward_by_ids(sp_std::iter::once((author, BLOCK_AUTHORING_POINTS)));
+    }
+}
+
+/// Drives era rotation from session boundaries and hands the selected validators to
+/// pallet-session.
+///
+/// When a session is planned after the current era has lasted `CurrentEraDuration` blocks, a new
+/// era is started and its validator set returned for that session. An era forced with
+/// `force_new_era` has already started in `on_initialize`, so its set is returned for the first
+/// session planned after it.
+/// The session index is recorded in `ErasStartSessionIndex` and the era becomes the `ActiveEra`
+/// when that session starts.
+/// The genesis session uses the era 0 set seeded by `GenesisConfig`, so this pallet's genesis
+/// must be built before pallet-session's.
+impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
+    fn new_session(new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+        let now = frame_system::Pallet::<T>::block_number();
+        let current_era = Pallet::<T>::current_era();
+        
+        let new_era = if Pallet::<T>::era_elapsed(now) {
+            Pallet::<T>::new_era(now)
+        } else if ErasStartSessionIndex::<T>::contains_key(current_era) {
+            return None;
+        } else {
+            // A forced era that started between session boundaries
+            current_era
+        };
+        ErasStartSessionIndex::<T>::insert(new_era, new_index);
+        
+        // Keep the current set if nobody could be selected
+        let validators = ErasValidatorList::<T>::get(new_era);
+        if validators.is_empty() {
+            return None;
+        }
         
-        /// Update the validator set for the next session.
-        fn update_validator_set() {
-            // Select validators for the next era
-            let selected_validators = Self::select_validators();
-            
-            // TODO: Integrate with pallet-session to update the validator set
+        Some(validators.into_inner())
+    }
+    
+    fn new_session_genesis(new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+        // The set of era 0 seeded by the genesis config, if any
+        let validators = ErasValidatorList::<T>::get(0);
+        if validators.is_empty() {
+            return None;
         }
+        
+        ErasStartSessionIndex::<T>::insert(0, new_index);
+        Some(validators.into_inner())
+    }
+    
+    fn start_session(start_index: SessionIndex) {
+        let current_era = Pallet::<T>::current_era();
+        
+        if ErasStartSessionIndex::<T>::get(current_era) == Some(start_index) {
+            ActiveEra::<T>::put(current_era);
+        }
+    }
+    
+    fn end_session(_end_index: SessionIndex) {}
+}
+
+/// Fee sink that routes transaction fees into the current era's reward.
+///
+/// Use as (part of) the runtime's fee handler, e.g. the `OnChargeTransaction` `OnUnbalanced`.
+/// The fee imbalance is dropped, burning it, and its value is recorded in `RewardFeePot`; the
+/// same amount is minted again when the era's rewards are paid out, so issuance is unchanged
+/// overall.
+pub struct RewardFeeSink<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config> OnUnbalanced<NegativeImbalanceOf<T>> for RewardFeeSink<T> {
+    fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<T>) {
+        RewardFeePot::<T>::mutate(|pot| *pot = pot.saturating_add(amount.peek()));
     }
 }
 
@@ -0,0 +1,294 @@
+//! Storage migrations for the staking atlas pallet.
+
+use super::*;
+use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+use sp_std::marker::PhantomData;
+
+const LOG_TARGET: &str = "runtime::staking-atlas";
+
+/// The `Delegation` layout of versions 1 and 2, before `compound` was added.
+#[derive(Encode, Decode)]
+struct V2Delegation<Balance> {
+    amount: Balance,
+    since_era: EraIndex,
+}
+
+/// The `Delegator` layout of versions 1 and 2.
+#[derive(Encode, Decode)]
+struct V2Delegator<AccountId, Balance> {
+    account: AccountId,
+    delegations: BTreeMap<AccountId, V2Delegation<Balance>>,
+    total_staked: Balance,
+}
+
+/// `Delegators` as laid out in versions 1 and 2, so that the earlier migrations still read and
+/// write the layout they were written for.
+#[frame_support::storage_alias]
+type V2Delegators<T: Config> = StorageMap<
+    Pallet<T>,
+    Twox64Concat,
+    <T as frame_system::Config>::AccountId,
+    V2Delegator<<T as frame_system::Config>::AccountId, BalanceOf<T>>,
+>;
+
+/// Migration to version 1: `Delegator::delegations` becomes a map of delegations keyed by
+/// validator.
+pub mod v1 {
+    use super::*;
+
+    /// The `Delegator` layout before version 1.
+    #[derive(Decode)]
+    struct OldDelegator<AccountId, Balance> {
+        account: AccountId,
+        delegations: Vec<(AccountId, Balance)>,
+        total_staked: Balance,
+    }
+
+    /// Translate every `Delegators` entry from the old `Vec` layout to the bounded map.
+    ///
+    /// Duplicate entries for the same validator are merged. `delegate` never allowed more than
+    /// `MaxDelegationsPerDelegator` entries, so no delegation is expected to be dropped; the map
+    /// is bounded again by `MigrateToV3`.
+    pub struct MigrateToV1<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() >= 1 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut translated = 0u64;
+            V2Delegators::<T>::translate::<OldDelegator<T::AccountId, BalanceOf<T>>, _>(|_, old| {
+                translated = translated.saturating_add(1);
+
+                let mut delegations = BTreeMap::new();
+                for (validator, amount) in old.delegations {
+                    let merged = delegations
+                        .get(&validator)
+                        .map(|delegation: &V2Delegation<BalanceOf<T>>| delegation.amount)
+                        .unwrap_or_else(Zero::zero);
+                    // Existing delegations predate the migration and are effective immediately.
+                    let delegation = V2Delegation { amount: merged.saturating_add(amount), since_era: 0 };
+                    delegations.insert(validator, delegation);
+                }
+
+                Some(V2Delegator {
+                    account: old.account,
+                    delegations,
+                    total_staked: old.total_staked,
+                })
+            });
+
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+        }
+    }
+}
+
+/// Migration to version 2: delegations are backed by reserves instead of the `delgatls` lock.
+pub mod v2 {
+    use super::*;
+
+    /// Remove the `delgatls` lock of every delegator and reserve its active and unbonding
+    /// delegations under `DELEGATION_RESERVE_ID` instead.
+    ///
+    /// The lock already held these funds out of the free balance, so the reserve is expected to
+    /// succeed. Should it fail, as much as the free balance allows is reserved and the shortfall
+    /// is taken off the account's delegations, then off its unbonding delegations, so that the
+    /// staking totals never count funds that are not held.
+    ///
+    /// Every account is visited once and touches at most `MaxDelegationsPerDelegator`
+    /// validators, which bounds the returned weight.
+    pub struct MigrateToV2<T>(PhantomData<T>);
+
+    impl<T: Config> MigrateToV2<T> {
+        /// The reads and writes of migrating one account, at most one validator per
+        /// delegation: the delegator, its unbonding delegations, the lock, the reserve and the
+        /// account itself.
+        fn account_weight() -> Weight {
+            let validators = T::MaxDelegationsPerDelegator::get() as u64;
+            T::DbWeight::get().reads_writes(validators.saturating_add(5), validators.saturating_add(5))
+        }
+
+        /// Swap the `delgatls` lock of `who` for a reserve of `delegated`, returning the part
+        /// that could not be reserved.
+        fn migrate_account(who: &T::AccountId, delegated: BalanceOf<T>) -> BalanceOf<T> {
+            T::Currency::remove_lock(LockIdentifier(*b"delgatls"), who);
+            if T::Currency::reserve_named(&DELEGATION_RESERVE_ID, who, delegated).is_ok() {
+                return Zero::zero();
+            }
+
+            let reservable = T::Currency::free_balance(who).min(delegated);
+            if T::Currency::reserve_named(&DELEGATION_RESERVE_ID, who, reservable).is_err() {
+                return delegated;
+            }
+            delegated.saturating_sub(reservable)
+        }
+
+        /// Take `shortfall` off the delegations of `who`, and the stake of the validators
+        /// they back, then off its unbonding delegations.
+        fn cut_shortfall(who: &T::AccountId, mut shortfall: BalanceOf<T>) {
+            log::warn!(
+                target: LOG_TARGET,
+                "could not reserve {:?} delegated by {:?}; reducing its delegations",
+                shortfall,
+                who,
+            );
+
+            V2Delegators::<T>::mutate_exists(who, |maybe_delegator| {
+                let delegator = match maybe_delegator {
+                    Some(delegator) => delegator,
+                    None => return,
+                };
+                for (validator, delegation) in delegator.delegations.iter_mut() {
+                    if shortfall.is_zero() {
+                        break;
+                    }
+                    let cut = delegation.amount.min(shortfall);
+                    delegation.amount = delegation.amount.saturating_sub(cut);
+                    delegator.total_staked = delegator.total_staked.saturating_sub(cut);
+                    shortfall = shortfall.saturating_sub(cut);
+
+                    Validators::<T>::mutate(validator, |maybe_validator| {
+                        if let Some(validator) = maybe_validator {
+                            validator.total_stake = validator.total_stake.saturating_sub(cut);
+                        }
+                    });
+                    if delegation.amount.is_zero() {
+                        ValidatorNominators::<T>::mutate(validator, |nominators| {
+                            nominators.retain(|nominator| nominator != who)
+                        });
+                    }
+                }
+
+                delegator.delegations.retain(|_, delegation| !delegation.amount.is_zero());
+                if delegator.delegations.is_empty() {
+                    *maybe_delegator = None;
+                }
+            });
+
+            if !shortfall.is_zero() {
+                UnbondingDelegations::<T>::mutate_exists(who, |chunks| {
+                    Pallet::<T>::slash_chunks(chunks, shortfall)
+                });
+            }
+        }
+    }
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() >= 2 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut migrated = 0u64;
+            for (who, delegator) in V2Delegators::<T>::iter() {
+                migrated = migrated.saturating_add(1);
+
+                let unbonding = Pallet::<T>::chunks_total(&UnbondingDelegations::<T>::get(&who));
+                let shortfall = Self::migrate_account(&who, delegator.total_staked.saturating_add(unbonding));
+                if !shortfall.is_zero() {
+                    Self::cut_shortfall(&who, shortfall);
+                }
+            }
+
+            // Accounts that undelegated everything only have unbonding delegations left. Those
+            // whose every delegation was cut above already hold their reserve.
+            for (who, chunks) in UnbondingDelegations::<T>::iter() {
+                if V2Delegators::<T>::contains_key(&who) ||
+                    !T::Currency::reserved_balance_named(&DELEGATION_RESERVE_ID, &who).is_zero()
+                {
+                    continue;
+                }
+                migrated = migrated.saturating_add(1);
+
+                let shortfall = Self::migrate_account(&who, Pallet::<T>::chunks_total(&chunks));
+                if !shortfall.is_zero() {
+                    Self::cut_shortfall(&who, shortfall);
+                }
+            }
+
+            StorageVersion::new(2).put::<Pallet<T>>();
+
+            Self::account_weight()
+                .saturating_mul(migrated)
+                .saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        }
+    }
+}
+
+/// Migration to version 3: every `Delegation` gains a `compound` flag, initially unset.
+pub mod v3 {
+    use super::*;
+
+    /// Translate every `Delegators` entry to the layout with `Delegation::compound`.
+    ///
+    /// The delegation map is bounded again; `delegate` never allowed more than
+    /// `MaxDelegationsPerDelegator` entries, so no delegation is expected to be dropped. Should
+    /// one not fit, it is logged and released: its reserve is returned and it is taken off the
+    /// delegator's and the validator's totals.
+    pub struct MigrateToV3<T>(PhantomData<T>);
+
+    impl<T: Config> MigrateToV3<T> {
+        /// Release a delegation of `amount` from `who` to `validator` that no longer fits the
+        /// bounded delegation map.
+        fn release_delegation(who: &T::AccountId, validator: &T::AccountId, amount: BalanceOf<T>) {
+            log::error!(
+                target: LOG_TARGET,
+                "delegation of {:?} to {:?} exceeds MaxDelegationsPerDelegator; releasing {:?}",
+                who,
+                validator,
+                amount,
+            );
+
+            T::Currency::unreserve_named(&DELEGATION_RESERVE_ID, who, amount);
+            Validators::<T>::mutate(validator, |maybe_validator| {
+                if let Some(validator) = maybe_validator {
+                    validator.total_stake = validator.total_stake.saturating_sub(amount);
+                }
+            });
+            ValidatorNominators::<T>::mutate(validator, |nominators| {
+                nominators.retain(|nominator| nominator != who)
+            });
+        }
+    }
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() >= 3 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut translated = 0u64;
+            Delegators::<T>::translate::<V2Delegator<T::AccountId, BalanceOf<T>>, _>(|who, old| {
+                translated = translated.saturating_add(1);
+
+                let mut delegations = BoundedBTreeMap::new();
+                let mut total_staked = old.total_staked;
+                for (validator, old_delegation) in old.delegations {
+                    let delegation = Delegation {
+                        amount: old_delegation.amount,
+                        since_era: old_delegation.since_era,
+                        compound: false,
+                        pending: None,
+                    };
+                    if delegations.try_insert(validator.clone(), delegation).is_err() {
+                        Self::release_delegation(&who, &validator, old_delegation.amount);
+                        total_staked = total_staked.saturating_sub(old_delegation.amount);
+                    }
+                }
+
+                Some(Delegator {
+                    account: old.account,
+                    delegations,
+                    total_staked,
+                })
+            });
+
+            StorageVersion::new(3).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! Test runtime for the staking atlas pallet.
+
+use crate as pallet_staking_atlas;
+use crate::*;
+use frame_support::{
+    parameter_types,
+    traits::{ConstU32, ConstU64, Everything, GenesisBuild, Hooks},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+    testing::{Header, UintAuthorityId},
+    traits::{BlakeTwo256, ConvertInto, Hash, IdentityLookup},
+    Perbill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub type AccountId = u64;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+
+frame_support::construct_runtime!(
+    pub enum Test where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        Staking: pallet_staking_atlas::{Pallet, Call, Storage, Config<T>, Event<T>},
+        Session: pallet_session::{Pallet, Call, Storage, Config<T>, Event},
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = BlockNumber;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+    // Varied by individual tests
+    pub static ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ConstU32<50>;
+    type MaxReserves = ConstU32<50>;
+    type ReserveIdentifier = [u8; 8];
+    type Balance = Balance;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const SessionPeriod: BlockNumber = 5;
+    pub const SessionOffset: BlockNumber = 0;
+}
+
+impl pallet_session::Config for Test {
+    type Event = Event;
+    type ValidatorId = AccountId;
+    type ValidatorIdOf = ConvertInto;
+    type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+    type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+    type SessionManager = Staking;
+    type SessionHandler = pallet_session::TestSessionHandler;
+    type Keys = UintAuthorityId;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const EraDuration: BlockNumber = 10;
+    pub const MinEraDuration: BlockNumber = 5;
+    pub const MaxEraDuration: BlockNumber = 100;
+    pub const ValidatorsCount: u32 = 3;
+    pub const MaxValidatorsCount: u32 = 10;
+    pub const MinActiveValidators: u32 = 1;
+    pub const MaxCandidates: u32 = 20;
+    pub const MinValidatorStake: Balance = 100;
+    pub const MinDelegationStake: Balance = 10;
+    pub const MaxDelegationsPerDelegator: u32 = 4;
+    pub const RewardPaymentDelay: EraIndex = 1;
+    pub const HistoryDepth: EraIndex = 4;
+    pub const RewardExpiryWarning: EraIndex = 1;
+    pub const BondingDuration: EraIndex = 2;
+    pub const MaxUnlockingChunks: u32 = 8;
+    pub const ReputationWeight: Perbill = Perbill::from_percent(20);
+    pub const MaxReputationScore: Balance = 100;
+    pub const ReputationDecay: Perbill = Perbill::from_percent(50);
+    pub const StakeDecreaseCooldown: EraIndex = 1;
+    pub const MaxStakeMultiplier: u32 = 10;
+    pub const MaxCommission: Perbill = Perbill::from_percent(50);
+    pub const EraReward: Balance = 1_000;
+    pub const JailDuration: EraIndex = 2;
+
+    // Varied by individual tests
+    pub static MaxNominatorsPerValidator: u32 = 4;
+    pub static Oversubscription: OversubscriptionPolicy = OversubscriptionPolicy::Reject;
+    pub static MinDelegationEras: EraIndex = 0;
+    pub static MaxTotalSupply: Balance = 1_000_000_000;
+    pub static SlashDeferDuration: EraIndex = 0;
+    pub static NewValidatorSets: Vec<(EraIndex, Vec<AccountId>)> = vec![];
+    pub static DustRemoved: Balance = 0;
+    pub static MaxRewardsSweptPerEra: u32 = 100;
+    pub static RandomSeed: u64 = 0;
+    pub static Gateways: Vec<AccountId> = vec![];
+    pub static MinSelfStakeRatio: Perbill = Perbill::from_percent(10);
+}
+
+/// Randomness derived from the subject and `RandomSeed` alone, so tests are deterministic.
+pub struct TestRandomness;
+impl frame_support::traits::Randomness<H256, BlockNumber> for TestRandomness {
+    fn random(subject: &[u8]) -> (H256, BlockNumber) {
+        (BlakeTwo256::hash_of(&(subject, RandomSeed::get())), System::block_number())
+    }
+}
+
+/// Records every validator set handed to `OnNewValidatorSet`.
+pub struct RecordValidatorSets;
+impl OnNewValidatorSet<AccountId> for RecordValidatorSets {
+    fn on_new_validator_set(era: EraIndex, validators: &[AccountId]) {
+        NewValidatorSets::mutate(|sets| sets.push((era, validators.to_vec())));
+    }
+}
+
+/// Records the total amount handed to `DustRemoval`.
+pub struct RecordDust;
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for RecordDust {
+    fn on_nonzero_unbalanced(dust: NegativeImbalanceOf<Test>) {
+        DustRemoved::mutate(|total| *total += dust.peek());
+    }
+}
+
+/// Reports the accounts in `Gateways` as `Gateway` and every other account as `Normal`.
+pub struct TestAccountTypes;
+impl AccountTypeProvider<AccountId> for TestAccountTypes {
+    fn account_type(who: &AccountId) -> AccountType {
+        if Gateways::get().contains(who) {
+            AccountType::Gateway
+        } else {
+            AccountType::Normal
+        }
+    }
+}
+
+impl Config for Test {
+    type Event = Event;
+    type Currency = Balances;
+    type EraDuration = EraDuration;
+    type MinEraDuration = MinEraDuration;
+    type MaxEraDuration = MaxEraDuration;
+    type ValidatorsCount = ValidatorsCount;
+    type MaxValidatorsCount = MaxValidatorsCount;
+    type MinActiveValidators = MinActiveValidators;
+    type MaxCandidates = MaxCandidates;
+    type MinValidatorStake = MinValidatorStake;
+    type MinDelegationStake = MinDelegationStake;
+    type MaxDelegationsPerDelegator = MaxDelegationsPerDelegator;
+    type MaxNominatorsPerValidator = MaxNominatorsPerValidator;
+    type OversubscriptionPolicy = Oversubscription;
+    type MinDelegationEras = MinDelegationEras;
+    type RewardPaymentDelay = RewardPaymentDelay;
+    type HistoryDepth = HistoryDepth;
+    type RewardExpiryWarning = RewardExpiryWarning;
+    type MaxRewardsSweptPerEra = MaxRewardsSweptPerEra;
+    type BondingDuration = BondingDuration;
+    type MaxUnlockingChunks = MaxUnlockingChunks;
+    type ReputationWeight = ReputationWeight;
+    type MaxReputationScore = MaxReputationScore;
+    type ReputationDecay = ReputationDecay;
+    type MinSelfStakeRatio = MinSelfStakeRatio;
+    type StakeDecreaseCooldown = StakeDecreaseCooldown;
+    type MaxStakeMultiplier = MaxStakeMultiplier;
+    type MaxCommission = MaxCommission;
+    type MaxValidatorNameLength = ConstU32<16>;
+    type MaxValidatorWebsiteLength = ConstU32<32>;
+    type MaxValidatorContactLength = ConstU32<32>;
+    type EraReward = EraReward;
+    type MaxTotalSupply = MaxTotalSupply;
+    type JailDuration = JailDuration;
+    type SlashDeferDuration = SlashDeferDuration;
+    type SlashCancelOrigin = EnsureRoot<AccountId>;
+    type AccountTypes = TestAccountTypes;
+    type Randomness = TestRandomness;
+    type OnNewValidatorSet = RecordValidatorSets;
+    type Slash = ();
+    type DustRemoval = RecordDust;
+    type WeightInfo = ();
+}
+
+/// The self-stake of the validators registered at genesis.
+pub const GENESIS_STAKE: Balance = 1_000;
+
+/// The free balance every endowed account starts with.
+pub const INITIAL_BALANCE: Balance = 100_000;
+
+pub struct ExtBuilder {
+    validators: Vec<(AccountId, Balance)>,
+    endowed: Vec<AccountId>,
+}
+
+impl Default for ExtBuilder {
+    fn default() -> Self {
+        Self {
+            validators: vec![(10, GENESIS_STAKE), (20, GENESIS_STAKE), (30, GENESIS_STAKE)],
+            endowed: vec![1, 2, 3, 4, 5, 10, 20, 30, 40, 50],
+        }
+    }
+}
+
+impl ExtBuilder {
+    /// Replace the validators registered at genesis.
+    pub fn validators(mut self, validators: Vec<(AccountId, Balance)>) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    pub fn max_nominators(self, max: u32) -> Self {
+        MaxNominatorsPerValidator::set(&max);
+        self
+    }
+
+    pub fn oversubscription(self, policy: OversubscriptionPolicy) -> Self {
+        Oversubscription::set(&policy);
+        self
+    }
+
+    pub fn min_delegation_eras(self, eras: EraIndex) -> Self {
+        MinDelegationEras::set(&eras);
+        self
+    }
+
+    pub fn max_total_supply(self, supply: Balance) -> Self {
+        MaxTotalSupply::set(&supply);
+        self
+    }
+
+    pub fn slash_defer_duration(self, eras: EraIndex) -> Self {
+        SlashDeferDuration::set(&eras);
+        self
+    }
+
+    pub fn max_rewards_swept(self, max: u32) -> Self {
+        MaxRewardsSweptPerEra::set(&max);
+        self
+    }
+
+    pub fn min_self_stake_ratio(self, ratio: Perbill) -> Self {
+        MinSelfStakeRatio::set(&ratio);
+        self
+    }
+
+    pub fn build(self) -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+        pallet_balances::GenesisConfig::<Test> {
+            balances: self.endowed.iter().map(|who| (*who, INITIAL_BALANCE)).collect(),
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        pallet_staking_atlas::GenesisConfig::<Test> { initial_validators: self.validators }
+            .assimilate_storage(&mut storage)
+            .unwrap();
+
+        // Every endowed account has session keys, so any of them can be handed a session
+        pallet_session::GenesisConfig::<Test> {
+            keys: self.endowed.iter().map(|who| (*who, *who, UintAuthorityId(*who))).collect(),
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        let mut ext = sp_io::TestExternalities::new(storage);
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    pub fn build_and_execute(self, test: impl FnOnce()) {
+        self.build().execute_with(test);
+    }
+}
+
+/// Advance to block `n`, running the staking and session hooks of every block on the way.
+///
+/// Sessions rotate every `SessionPeriod` blocks, and the staking pallet starts a new era at the
+/// first session boundary at which the current era has elapsed.
+pub fn run_to_block(n: BlockNumber) {
+    while System::block_number() < n {
+        Staking::on_finalize(System::block_number());
+        System::set_block_number(System::block_number() + 1);
+        Staking::on_initialize(System::block_number());
+        Session::on_initialize(System::block_number());
+    }
+}
+
+/// Run blocks until a session rotation starts the next era.
+pub fn start_next_era() -> EraIndex {
+    let current_era = Staking::current_era();
+    while Staking::current_era() == current_era {
+        run_to_block(System::block_number() + 1);
+    }
+    Staking::current_era()
+}
+
+/// Start eras until `era` is the current era.
+pub fn start_era(era: EraIndex) {
+    while Staking::current_era() < era {
+        start_next_era();
+    }
+}
+
+/// Let `author` author `blocks` blocks of the current era.
+pub fn author_blocks(author: AccountId, blocks: u32) {
+    for _ in 0..blocks {
+        <Staking as pallet_authorship::EventHandler<AccountId, BlockNumber>>::note_author(author);
+    }
+}
+
+/// Validator metadata with the given name and empty website and contact.
+pub fn metadata(name: &[u8]) -> ValidatorDetailsOf<Test> {
+    ValidatorDetails {
+        name: BoundedVec::truncate_from(name.to_vec()),
+        website: Default::default(),
+        contact: Default::default(),
+    }
+}
+
+/// The events of this pallet deposited so far.
+pub fn staking_events() -> Vec<pallet_staking_atlas::Event<Test>> {
+    System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::Staking(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The amount of the `stakeatls` lock of `who`, zero without one.
+pub fn stake_lock(who: AccountId) -> Balance {
+    Balances::locks(who)
+        .iter()
+        .find(|lock| lock.id == LockIdentifier(*b"stakeatls"))
+        .map(|lock| lock.amount)
+        .unwrap_or(0)
+}
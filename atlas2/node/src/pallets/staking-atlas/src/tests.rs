@@ -0,0 +1,1529 @@
+//! Tests for the staking atlas pallet.
+
+use crate::{mock::*, Event as StakingEvent, *};
+use frame_support::{assert_noop, assert_ok, traits::Currency as _};
+use sp_runtime::traits::BadOrigin;
+
+#[test]
+fn reaching_the_nominator_cap_emits_validator_full_and_closes_delegations() {
+    ExtBuilder::default().max_nominators(2).build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert!(Staking::validators(10).unwrap().delegations_open);
+        assert!(!staking_events().contains(&StakingEvent::ValidatorFull(10)));
+        
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 100));
+        assert!(staking_events().contains(&StakingEvent::ValidatorFull(10)));
+        assert!(!Staking::validators(10).unwrap().delegations_open);
+        
+        assert_noop!(
+            Staking::delegate(Origin::signed(3), 10, 100),
+            Error::<Test>::DelegationsClosed
+        );
+        
+        // An existing delegator may still top up
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 50));
+    });
+}
+
+#[test]
+fn resync_era_rolls_back_eras_started_after_the_block() {
+    ExtBuilder::default().build_and_execute(|| {
+        start_era(2);
+        let era_two_start = Staking::era_start_block_number(2);
+        assert!(!Staking::eras_validator_list(2).is_empty());
+        
+        assert_noop!(Staking::resync_era(Origin::signed(1), era_two_start - 1), BadOrigin);
+        assert_ok!(Staking::resync_era(Origin::root(), era_two_start - 1));
+        
+        assert_eq!(Staking::current_era(), 1);
+        assert!(!EraStartBlockNumber::<Test>::contains_key(2));
+        assert!(Staking::eras_validator_list(2).is_empty());
+        assert!(!Staking::eras_validator_list(1).is_empty());
+        assert!(staking_events().contains(&StakingEvent::EraResynced(1)));
+        
+        // Resyncing to a block within the current era changes nothing
+        assert_ok!(Staking::resync_era(Origin::root(), era_two_start));
+        assert_eq!(Staking::current_era(), 1);
+    });
+}
+
+#[test]
+fn reward_minting_is_throttled_at_the_supply_cap() {
+    ExtBuilder::default().max_total_supply(1_000_100).build_and_execute(|| {
+        assert_eq!(Balances::total_issuance(), 1_000_000);
+        
+        // Validator 10 earns the whole reward of era 0, claimable from era 2
+        author_blocks(10, 10);
+        start_era(2);
+        assert_eq!(Staking::eras_validator_reward(0, 10).unwrap().amount, EraReward::get());
+        
+        let before = Balances::free_balance(10);
+        assert_ok!(Staking::payout_stakers(Origin::signed(1), 10, 0));
+        
+        assert_eq!(Balances::free_balance(10), before + 100);
+        assert_eq!(Balances::total_issuance(), 1_000_100);
+        assert!(staking_events().contains(&StakingEvent::SupplyCapReached(EraReward::get() - 100)));
+    });
+}
+
+#[test]
+fn only_long_deregistered_validators_without_stake_can_be_reaped() {
+    ExtBuilder::default().build_and_execute(|| {
+        // A full slash leaves 10 registered with nothing bonded
+        assert_ok!(Staking::set_payee(Origin::signed(10), RewardDestination::Account(1)));
+        assert_ok!(Staking::slash_validator(Origin::root(), 10, Perbill::one()));
+        assert_eq!(Staking::validators(10).unwrap().self_stake, 0);
+        assert_ok!(Staking::deregister_validator(Origin::signed(10), false));
+        assert_ok!(Staking::deregister_validator(Origin::signed(20), false));
+        assert_noop!(
+            Staking::reap_stale_validator(Origin::signed(1), 10),
+            Error::<Test>::ValidatorNotStale
+        );
+        
+        start_era(BondingDuration::get() + 1);
+        
+        // 20 still has its self-stake bonded and 30 is active
+        assert_noop!(
+            Staking::reap_stale_validator(Origin::signed(1), 20),
+            Error::<Test>::ValidatorNotStale
+        );
+        assert_noop!(
+            Staking::reap_stale_validator(Origin::signed(1), 30),
+            Error::<Test>::ValidatorNotStale
+        );
+        
+        assert_ok!(Staking::reap_stale_validator(Origin::signed(1), 10));
+        assert!(Staking::validators(10).is_none());
+        assert_eq!(Staking::jailed(10), None);
+        assert_eq!(Staking::payee(10), None);
+        assert_eq!(stake_lock(10), 0);
+        assert!(staking_events().contains(&StakingEvent::ValidatorReaped(10)));
+    });
+}
+
+#[test]
+fn a_jailed_validator_is_unselectable_until_unjailed() {
+    ExtBuilder::default().build_and_execute(|| {
+        Staking::jail_validator(&10);
+        assert_eq!(Staking::jailed(10), Some(JailDuration::get()));
+        
+        start_next_era();
+        assert!(!Staking::eras_validator_list(1).contains(&10));
+        assert_noop!(Staking::unjail(Origin::signed(10)), Error::<Test>::StillJailed);
+        
+        start_next_era();
+        assert!(!Staking::eras_validator_list(2).contains(&10));
+        assert_ok!(Staking::unjail(Origin::signed(10)));
+        assert!(!Staking::is_jailed(&10));
+        
+        start_next_era();
+        assert!(Staking::eras_validator_list(3).contains(&10));
+    });
+}
+
+#[test]
+fn a_slashed_validator_tops_up_and_is_selected_again_after_unjailing() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::slash_validator(Origin::root(), 10, Perbill::from_percent(95)));
+        assert!(Staking::is_jailed(&10));
+        assert!(staking_events().contains(&StakingEvent::ValidatorJailed(10, JailDuration::get())));
+        assert_noop!(Staking::unjail(Origin::signed(10)), Error::<Test>::StillJailed);
+        
+        start_era(JailDuration::get());
+        assert!(!Staking::eras_validator_list(JailDuration::get()).contains(&10));
+        
+        // The slash left the self-stake below the minimum
+        assert_eq!(Staking::validators(10).unwrap().self_stake, GENESIS_STAKE / 20);
+        assert_noop!(Staking::unjail(Origin::signed(10)), Error::<Test>::InsufficientStake);
+        assert_ok!(Staking::increase_stake(Origin::signed(10), MinValidatorStake::get()));
+        
+        assert_ok!(Staking::unjail(Origin::signed(10)));
+        assert!(!Staking::is_jailed(&10));
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Active);
+        assert!(staking_events().contains(&StakingEvent::ValidatorUnjailed(10)));
+        
+        start_next_era();
+        assert!(Staking::eras_validator_list(JailDuration::get() + 1).contains(&10));
+    });
+}
+
+#[test]
+fn delegations_are_keyed_by_validator_with_a_cached_total() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::delegate(Origin::signed(1), 20, 50));
+        
+        let delegator = Staking::delegators(1).unwrap();
+        assert_eq!(delegator.delegations.len(), 2);
+        assert_eq!(delegator.delegations.get(&10).map(|d| d.amount), Some(100));
+        assert_eq!(delegator.delegations.get(&20).map(|d| d.amount), Some(50));
+        assert_eq!(delegator.total_staked, 150);
+        
+        start_next_era();
+        assert_ok!(Staking::undelegate(Origin::signed(1), 20, 50));
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 40));
+        
+        let delegator = Staking::delegators(1).unwrap();
+        assert!(delegator.delegations.get(&20).is_none());
+        assert_eq!(delegator.delegations.get(&10).map(|d| d.amount), Some(60));
+        assert_eq!(delegator.total_staked, 60);
+        assert_eq!(Staking::validators(10).unwrap().total_stake, GENESIS_STAKE + 60);
+        assert_eq!(Staking::validators(20).unwrap().total_stake, GENESIS_STAKE);
+        
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 60));
+        assert!(Staking::delegators(1).is_none());
+    });
+}
+
+#[test]
+fn on_new_validator_set_receives_every_new_set() {
+    ExtBuilder::default().build_and_execute(|| {
+        start_next_era();
+        start_next_era();
+        
+        let sets = NewValidatorSets::get();
+        assert_eq!(sets.iter().map(|(era, _)| *era).collect::<Vec<_>>(), vec![1, 2]);
+        for (era, mut set) in sets {
+            set.sort();
+            assert_eq!(set, vec![10, 20, 30]);
+            assert_eq!(Staking::eras_validator_list(era).len(), 3);
+        }
+    });
+}
+
+#[test]
+fn nominators_of_lists_every_backer_with_its_amount() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 250));
+        assert_ok!(Staking::delegate(Origin::signed(2), 20, 50));
+        
+        let mut nominators = Staking::nominators_of(&10);
+        nominators.sort();
+        assert_eq!(nominators, vec![(1, 100), (2, 250)]);
+        assert_eq!(Staking::nominators_of(&20), vec![(2, 50)]);
+        assert!(Staking::nominators_of(&30).is_empty());
+    });
+}
+
+#[test]
+fn delegations_to_two_validators_reserve_the_combined_amount() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::delegate(Origin::signed(1), 20, 50));
+        
+        assert_eq!(Balances::reserved_balance(1), 150);
+        assert_eq!(Balances::reserved_balance_named(&DELEGATION_RESERVE_ID, &1), 150);
+        assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 150);
+        assert_eq!(Staking::delegators(1).unwrap().total_staked, 150);
+    });
+}
+
+#[test]
+fn selection_respects_the_validator_count_bound() {
+    ExtBuilder::default()
+        .validators(vec![(10, 1_000), (20, 2_000), (30, 3_000), (40, 4_000), (50, 5_000)])
+        .build_and_execute(|| {
+            start_next_era();
+            
+            let selected = Staking::eras_validator_list(1);
+            assert_eq!(selected.len() as u32, ValidatorsCount::get().min(MaxValidatorsCount::get()));
+            assert_eq!(selected.into_inner(), vec![50, 40, 30]);
+        });
+}
+
+#[test]
+fn self_bond_ratio_reflects_the_stake_split() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_eq!(Staking::self_bond_ratio(&10), Some(Perbill::one()));
+        
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 3_000));
+        assert_eq!(Staking::self_bond_ratio(&10), Some(Perbill::from_percent(25)));
+        
+        assert_eq!(Staking::self_bond_ratio(&1), None);
+    });
+}
+
+#[test]
+fn a_failed_payout_is_recorded_and_retried_up_to_the_supply_cap() {
+    ExtBuilder::default().max_total_supply(1_000_100).build_and_execute(|| {
+        assert_ok!(Staking::set_payee(Origin::signed(10), RewardDestination::Account(99)));
+        author_blocks(10, 10);
+        start_era(2);
+        
+        // The payee account cannot be created below the existential deposit
+        ExistentialDeposit::set(&(EraReward::get() + 1));
+        assert_ok!(Staking::payout_stakers(Origin::signed(1), 10, 0));
+        assert_eq!(Staking::failed_payouts(0, 10), EraReward::get());
+        assert!(staking_events().contains(&StakingEvent::PayoutFailed(10, 0, EraReward::get())));
+        
+        // Only the headroom below the cap is minted; the rest stays owed
+        ExistentialDeposit::set(&1);
+        assert_ok!(Staking::retry_payout(Origin::signed(1), 10, 0));
+        assert_eq!(Balances::free_balance(99), 100);
+        assert_eq!(Staking::failed_payouts(0, 10), EraReward::get() - 100);
+        assert!(staking_events().contains(&StakingEvent::PayoutRetried(10, 0, 100)));
+        
+        MaxTotalSupply::set(&1_000_000_000);
+        assert_ok!(Staking::retry_payout(Origin::signed(1), 10, 0));
+        assert_eq!(Balances::free_balance(99), EraReward::get());
+        assert_eq!(Staking::failed_payouts(0, 10), 0);
+        assert_noop!(
+            Staking::retry_payout(Origin::signed(1), 10, 0),
+            Error::<Test>::NoFailedPayout
+        );
+    });
+}
+
+#[test]
+fn reward_destination_defaults_to_stash_and_resolves_an_explicit_account() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_eq!(Staking::reward_destination(&10), RewardDestination::Stash);
+        
+        assert_ok!(Staking::set_payee(Origin::signed(10), RewardDestination::Account(99)));
+        assert_eq!(Staking::reward_destination(&10), RewardDestination::Account(99));
+        
+        author_blocks(10, 10);
+        start_era(2);
+        let stash_balance = Balances::free_balance(10);
+        assert_ok!(Staking::payout_stakers(Origin::signed(1), 10, 0));
+        
+        assert_eq!(Balances::free_balance(99), EraReward::get());
+        assert_eq!(Balances::free_balance(10), stash_balance);
+    });
+}
+
+#[test]
+fn raising_the_minimum_stake_excludes_the_validator_next_era() {
+    ExtBuilder::default()
+        .validators(vec![(10, 1_000), (20, 3_000), (30, 3_000)])
+        .build_and_execute(|| {
+            assert_ok!(Staking::set_minimum_validator_stake(Origin::root(), 2_000));
+            assert_eq!(Staking::validator_status(10), ValidatorStatus::Active);
+            
+            start_next_era();
+            
+            assert_eq!(Staking::validator_status(10), ValidatorStatus::InsufficientStake);
+            let mut selected = Staking::eras_validator_list(1).into_inner();
+            selected.sort();
+            assert_eq!(selected, vec![20, 30]);
+        });
+}
+
+#[test]
+fn validator_registered_carries_the_registration_context() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::register_validator(Origin::signed(40), 500, Perbill::zero()));
+        assert!(staking_events().contains(&StakingEvent::ValidatorRegistered {
+            who: 40,
+            self_stake: 500,
+            era: 0,
+        }));
+        
+        start_next_era();
+        assert_ok!(Staking::register_validator(Origin::signed(50), 700, Perbill::zero()));
+        assert!(staking_events().contains(&StakingEvent::ValidatorRegistered {
+            who: 50,
+            self_stake: 700,
+            era: 1,
+        }));
+    });
+}
+
+#[test]
+fn reward_rounding_dust_goes_to_the_dust_handler() {
+    ExtBuilder::default().build_and_execute(|| {
+        // Thirds of the era reward do not divide evenly
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, GENESIS_STAKE));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, GENESIS_STAKE));
+        start_era(1);
+        author_blocks(10, 10);
+        start_era(3);
+        
+        // Era 0 earned no points, so all of its reward was already handed over
+        assert_eq!(DustRemoved::get(), EraReward::get());
+        DustRemoved::set(&0);
+        assert_ok!(Staking::payout_stakers(Origin::signed(1), 10, 1));
+        
+        let share = Perbill::from_rational(1u32, 3u32) * EraReward::get();
+        assert_eq!(DustRemoved::get(), EraReward::get() - 3 * share);
+        assert_eq!(DustRemoved::get(), 1);
+    });
+}
+
+#[test]
+fn a_delegation_or_top_up_made_mid_era_earns_only_from_the_next_era() {
+    ExtBuilder::default().min_delegation_eras(1).build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 1_000));
+        assert!(Staking::eras_stakers(0, 10).delegations.is_empty());
+        
+        start_next_era();
+        assert_eq!(Staking::eras_stakers(1, 10).total, GENESIS_STAKE + 1_000);
+        
+        // The top-up is staked at once but only exposed from era 2
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 500));
+        let delegation = Staking::delegators(1).unwrap().delegations.get(&10).cloned().unwrap();
+        assert_eq!(delegation.amount, 1_500);
+        assert_eq!(delegation.pending, Some((2, 500)));
+        assert_eq!(delegation.effective_amount(1), 1_000);
+        assert_eq!(Staking::validators(10).unwrap().total_stake, GENESIS_STAKE + 1_500);
+        
+        start_next_era();
+        assert_eq!(Staking::eras_stakers(2, 10).total, GENESIS_STAKE + 1_500);
+        
+        // The top-up has not been held for `MinDelegationEras` yet
+        assert_noop!(
+            Staking::undelegate(Origin::signed(1), 10, 1_500),
+            Error::<Test>::DelegationTooYoung
+        );
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 1_000));
+        
+        start_next_era();
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 500));
+        assert!(Staking::delegators(1).is_none());
+    });
+}
+
+#[test]
+fn uptime_is_the_share_of_expected_blocks_authored() {
+    ExtBuilder::default().build_and_execute(|| {
+        // Era 0: everyone authors its share of 9 blocks
+        for validator in [10, 20, 30] {
+            author_blocks(validator, 3);
+        }
+        start_next_era();
+        
+        // Era 1: validator 10 misses all of its 4 expected blocks
+        author_blocks(20, 6);
+        author_blocks(30, 6);
+        start_next_era();
+        
+        assert_eq!(Staking::uptime(&10, 2), Perbill::from_rational(3u32, 7u32));
+        assert_eq!(Staking::uptime(&10, 1), Perbill::zero());
+        // Authoring more than the expected share does not count beyond 100%
+        assert_eq!(Staking::uptime(&20, 2), Perbill::one());
+        // Validators never selected have no uptime
+        assert_eq!(Staking::uptime(&40, 2), Perbill::zero());
+    });
+}
+
+#[test]
+fn a_compounding_validator_does_not_compound_its_cash_delegators() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::set_payee(Origin::signed(10), RewardDestination::Compound));
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, GENESIS_STAKE));
+        start_era(1);
+        author_blocks(10, 10);
+        start_era(3);
+        
+        let delegator_free = Balances::free_balance(1);
+        assert_ok!(Staking::payout_stakers(Origin::signed(1), 10, 1));
+        
+        // Equal stakes split the reward in half
+        let half = EraReward::get() / 2;
+        let validator = Staking::validators(10).unwrap();
+        assert_eq!(validator.self_stake, GENESIS_STAKE + half);
+        assert_eq!(stake_lock(10), GENESIS_STAKE + half);
+        
+        assert_eq!(Balances::free_balance(1), delegator_free + half);
+        assert_eq!(Staking::delegators(1).unwrap().delegations.get(&10).unwrap().amount, GENESIS_STAKE);
+        assert_eq!(validator.total_stake, 2 * GENESIS_STAKE + half);
+    });
+}
+
+#[test]
+fn a_declared_validator_sets_up_before_registering() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::declare_intent(Origin::signed(40)));
+        assert_eq!(Staking::validator_status(40), ValidatorStatus::Declared);
+        assert_noop!(Staking::declare_intent(Origin::signed(40)), Error::<Test>::AlreadyDeclared);
+        
+        // Identity can be set up without stake
+        assert_ok!(Staking::set_validator_metadata(Origin::signed(40), metadata(b"forty")));
+        
+        // Stake-requiring calls are rejected
+        assert_noop!(Staking::increase_stake(Origin::signed(40), 100), Error::<Test>::NotBonded);
+        assert_noop!(Staking::delegate(Origin::signed(1), 40, 100), Error::<Test>::NotBonded);
+        
+        assert_ok!(Staking::register_validator(Origin::signed(40), 500, Perbill::zero()));
+        assert_eq!(Staking::validator_status(40), ValidatorStatus::Active);
+        assert_eq!(Staking::validators(40).unwrap().self_stake, 500);
+        assert_eq!(Staking::validator_metadata(40), Some(metadata(b"forty")));
+        assert_eq!(stake_lock(40), 500);
+    });
+}
+
+#[test]
+fn collected_fees_augment_the_ending_era_reward() {
+    ExtBuilder::default().build_and_execute(|| {
+        RewardFeeSink::<Test>::on_unbalanced(Balances::issue(150));
+        RewardFeeSink::<Test>::on_unbalanced(Balances::issue(100));
+        assert_eq!(Staking::reward_fee_pot(), 250);
+        // The fees themselves are burnt until the reward is paid out
+        assert_eq!(Balances::total_issuance(), 1_000_000);
+        
+        start_next_era();
+        
+        assert_eq!(Staking::eras_reward(0), Some(EraReward::get() + 250));
+        assert_eq!(Staking::reward_fee_pot(), 0);
+        assert!(staking_events().contains(&StakingEvent::FeesAddedToReward(0, 250)));
+    });
+}
+
+#[test]
+fn undelegating_before_the_minimum_holding_period_is_rejected() {
+    ExtBuilder::default().min_delegation_eras(2).build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        
+        // Effective from era 1, so withdrawable from era 3
+        start_era(2);
+        assert_noop!(
+            Staking::undelegate(Origin::signed(1), 10, 100),
+            Error::<Test>::DelegationTooYoung
+        );
+        
+        start_era(3);
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 100));
+        assert!(Staking::delegators(1).is_none());
+    });
+}
+
+#[test]
+fn effective_status_resolves_jail_chill_and_stake() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::chill(Origin::signed(20)));
+        Staking::jail_validator(&30);
+        assert_ok!(Staking::declare_intent(Origin::signed(40)));
+        assert_ok!(Staking::register_validator(Origin::signed(50), 500, Perbill::zero()));
+        assert_ok!(Staking::deregister_validator(Origin::signed(50), false));
+        
+        assert_eq!(Staking::effective_status(&10), ValidatorStatus::Active);
+        assert_eq!(Staking::effective_status(&20), ValidatorStatus::Chilled);
+        assert_eq!(Staking::effective_status(&30), ValidatorStatus::Jailed);
+        assert_eq!(Staking::effective_status(&40), ValidatorStatus::Declared);
+        assert_eq!(Staking::effective_status(&50), ValidatorStatus::Deregistered);
+        assert_eq!(Staking::effective_status(&1), ValidatorStatus::Deregistered);
+        
+        // A raised minimum shows at once, before the next rotation stores it
+        assert_ok!(Staking::set_minimum_validator_stake(Origin::root(), 2 * GENESIS_STAKE));
+        assert_eq!(Staking::effective_status(&10), ValidatorStatus::InsufficientStake);
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Active);
+        // Jail and chill take precedence over the stake shortfall
+        assert_eq!(Staking::effective_status(&20), ValidatorStatus::Chilled);
+        assert_eq!(Staking::effective_status(&30), ValidatorStatus::Jailed);
+    });
+}
+
+#[test]
+fn unbonding_self_stake_stays_locked_until_the_bonding_period_ends() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), 400));
+        
+        assert_eq!(Staking::validators(10).unwrap().self_stake, GENESIS_STAKE - 400);
+        assert_eq!(Staking::unbonding_stake(10).into_inner(), vec![(BondingDuration::get(), 400)]);
+        assert_eq!(stake_lock(10), GENESIS_STAKE);
+        assert_noop!(
+            Staking::withdraw_unbonded(Origin::signed(10)),
+            Error::<Test>::CannotWithdrawBeforeBondingPeriod
+        );
+        
+        start_era(BondingDuration::get() - 1);
+        assert_noop!(
+            Staking::withdraw_unbonded(Origin::signed(10)),
+            Error::<Test>::CannotWithdrawBeforeBondingPeriod
+        );
+        assert_eq!(stake_lock(10), GENESIS_STAKE);
+        
+        start_era(BondingDuration::get());
+        assert_ok!(Staking::withdraw_unbonded(Origin::signed(10)));
+        assert_eq!(stake_lock(10), GENESIS_STAKE - 400);
+        assert!(Staking::unbonding_stake(10).is_empty());
+        assert!(staking_events().contains(&StakingEvent::Withdrawn(10, 400)));
+    });
+}
+
+#[test]
+fn eras_stakers_snapshots_the_exposure_of_each_selected_validator() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 300));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 200));
+        
+        start_next_era();
+        
+        let exposure = Staking::eras_stakers(1, 10);
+        assert_eq!(exposure.own, GENESIS_STAKE);
+        assert_eq!(exposure.total, GENESIS_STAKE + 500);
+        assert_eq!(
+            exposure.delegations.into_inner(),
+            vec![IndividualExposure { who: 1, value: 300 }, IndividualExposure { who: 2, value: 200 }]
+        );
+        assert_eq!(Staking::eras_stakers(1, 20).total, GENESIS_STAKE);
+        assert_eq!(Staking::eras_total_stake(1), 3 * GENESIS_STAKE + 500);
+        
+        // Later changes leave the snapshot alone
+        assert_ok!(Staking::delegate(Origin::signed(3), 10, 100));
+        assert_eq!(Staking::eras_stakers(1, 10).total, GENESIS_STAKE + 500);
+    });
+}
+
+#[test]
+fn a_validator_minimum_above_the_global_one_rejects_smaller_delegations() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::set_validator_prefs(Origin::signed(10), ValidatorPrefs { min_delegation: Some(500) }));
+        
+        assert_noop!(
+            Staking::delegate(Origin::signed(1), 10, 499),
+            Error::<Test>::InsufficientDelegationStake
+        );
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 500));
+        
+        // Other validators keep the global minimum
+        assert_ok!(Staking::delegate(Origin::signed(2), 20, MinDelegationStake::get()));
+        
+        // A minimum below the global one has no effect
+        assert_ok!(Staking::set_validator_prefs(Origin::signed(20), ValidatorPrefs { min_delegation: Some(1) }));
+        assert_noop!(
+            Staking::delegate(Origin::signed(3), 20, MinDelegationStake::get() - 1),
+            Error::<Test>::InsufficientDelegationStake
+        );
+    });
+}
+
+#[test]
+fn an_authoring_validator_ends_the_era_with_a_higher_reputation() {
+    ExtBuilder::default().build_and_execute(|| {
+        // Validator 10 authors every block of era 0, far above its expected share
+        author_blocks(10, 3);
+        
+        start_next_era();
+        
+        let reputation = |who| Staking::validators(who).unwrap().reputation;
+        // Half of the capped performance, since the decayed previous score is zero
+        assert_eq!(reputation(10).score, ReputationDecay::get().left_from_one() * MaxReputationScore::get());
+        assert_eq!(reputation(10).last_updated, 1);
+        assert!(reputation(10).score > reputation(20).score);
+        assert_eq!(reputation(20).score, 0);
+        assert!(staking_events().contains(&StakingEvent::ReputationUpdated(10, reputation(10).score)));
+    });
+}
+
+#[test]
+fn an_active_validator_decreases_within_the_self_stake_ratio_once_per_cooldown() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 4_000));
+        
+        // Deregistering is required to withdraw everything
+        assert_noop!(
+            Staking::decrease_stake(Origin::signed(10), GENESIS_STAKE),
+            Error::<Test>::InsufficientStake
+        );
+        // 400 of 4400 would breach the 10% self-stake ratio
+        assert_noop!(
+            Staking::decrease_stake(Origin::signed(10), 600),
+            Error::<Test>::SelfStakeRatioTooLow
+        );
+        
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), 500));
+        let validator = Staking::validators(10).unwrap();
+        assert!(validator.is_active);
+        assert_eq!(validator.self_stake, 500);
+        assert_eq!(validator.total_stake, 4_500);
+        assert_eq!(Staking::unbonding_stake(10).into_inner(), vec![(BondingDuration::get(), 500)]);
+        
+        assert_noop!(
+            Staking::decrease_stake(Origin::signed(10), 10),
+            Error::<Test>::StakeDecreaseInCooldown
+        );
+        
+        start_next_era();
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), 50));
+        assert_eq!(Staking::validators(10).unwrap().self_stake, 450);
+    });
+}
+
+#[test]
+fn a_slash_shrinks_self_and_delegated_stake_proportionally_and_excludes_the_validator() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 500));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 200));
+        let issuance = Balances::total_issuance();
+        
+        assert_ok!(Staking::slash_validator(Origin::root(), 10, Perbill::from_percent(10)));
+        
+        let validator = Staking::validators(10).unwrap();
+        assert_eq!(validator.self_stake, GENESIS_STAKE - 100);
+        assert_eq!(validator.total_stake, GENESIS_STAKE - 100 + 450 + 180);
+        assert!(!validator.is_active);
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Slashed);
+        assert_eq!(stake_lock(10), GENESIS_STAKE - 100);
+        
+        let delegated = |who| Staking::delegators(who).unwrap().delegations.get(&10).unwrap().amount;
+        assert_eq!(delegated(1), 450);
+        assert_eq!(delegated(2), 180);
+        assert_eq!(Balances::reserved_balance(1), 450);
+        assert_eq!(Balances::reserved_balance(2), 180);
+        
+        // `Slash` is `()`, so the slashed funds are burnt
+        assert_eq!(Balances::total_issuance(), issuance - 170);
+        assert!(staking_events().contains(&StakingEvent::ValidatorSlashed(10, 170)));
+        
+        start_next_era();
+        assert!(!Staking::eras_validator_list(1).contains(&10));
+        assert_noop!(Staking::delegate(Origin::signed(3), 10, 100), Error::<Test>::ValidatorNotActive);
+    });
+}
+
+#[test]
+fn a_new_session_at_the_era_boundary_returns_the_top_scoring_validators() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::register_validator(Origin::signed(40), 5_000, Perbill::zero()));
+        assert_ok!(Staking::register_validator(Origin::signed(50), 3_000, Perbill::zero()));
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 500));
+        
+        // Sessions within the era keep the genesis set
+        run_to_block(SessionPeriod::get());
+        assert_eq!(Staking::current_era(), 0);
+        let mut genesis_set = Session::validators();
+        genesis_set.sort();
+        assert_eq!(genesis_set, vec![10, 20, 30]);
+        
+        // The rotation at the era boundary queues the new set for the next session
+        start_next_era();
+        let set = vec![40, 50, 10];
+        assert_eq!(Staking::eras_validator_list(1).into_inner(), set);
+        assert_eq!(Session::queued_keys().into_iter().map(|(who, _)| who).collect::<Vec<_>>(), set);
+        assert_eq!(Staking::active_era(), 0);
+        
+        // The era becomes active once its first session starts
+        run_to_block(System::block_number() + SessionPeriod::get());
+        assert_eq!(Session::validators(), set);
+        assert_eq!(Staking::active_era(), 1);
+    });
+}
+
+#[test]
+fn claim_all_is_bounded_by_the_pairs_visited() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::delegate(Origin::signed(1), 20, 100));
+        
+        // Both validators earn in eras 1 and 2, claimable from era 3 and 4 respectively
+        for era in 1..=2 {
+            start_era(era);
+            author_blocks(10, 1);
+            author_blocks(20, 1);
+        }
+        start_era(4);
+        
+        // Three visits: 10 in eras 1, 2 and 3, of which only the first two pay out
+        let before = Balances::free_balance(1);
+        assert_ok!(Staking::claim_all(Origin::signed(1), 3));
+        assert!(staking_events().contains(&StakingEvent::AllClaimed(1, 2)));
+        assert!(ClaimedRewards::<Test>::get(1, 10) && ClaimedRewards::<Test>::get(2, 10));
+        assert!(!ClaimedRewards::<Test>::get(1, 20));
+        assert!(Balances::free_balance(1) > before);
+        
+        assert_ok!(Staking::claim_all(Origin::signed(1), 8));
+        assert!(staking_events().contains(&StakingEvent::AllClaimed(1, 2)));
+        assert!(ClaimedRewards::<Test>::get(1, 20) && ClaimedRewards::<Test>::get(2, 20));
+        
+        assert_ok!(Staking::claim_all(Origin::signed(1), 8));
+        assert!(staking_events().contains(&StakingEvent::AllClaimed(1, 0)));
+    });
+}
+
+#[test]
+fn selecting_again_within_an_era_changes_nothing() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 300));
+        start_next_era();
+        
+        let validators = Staking::eras_validator_list(1);
+        let exposure = Staking::eras_stakers(1, 10);
+        let total_stake = Staking::eras_total_stake(1);
+        let events = System::events().len();
+        
+        assert_eq!(Staking::select_validators(), validators);
+        
+        assert_eq!(Staking::eras_validator_list(1), validators);
+        assert_eq!(Staking::eras_stakers(1, 10), exposure);
+        assert_eq!(Staking::eras_total_stake(1), total_stake);
+        assert_eq!(System::events().len(), events);
+        
+        // The rotation itself handed out exactly one set for the era
+        let sets = NewValidatorSets::get();
+        assert_eq!(sets.iter().filter(|(era, _)| *era == 1).count(), 1);
+    });
+}
+
+#[test]
+fn a_gateway_account_cannot_register_as_a_validator() {
+    ExtBuilder::default().build_and_execute(|| {
+        Gateways::set(&vec![40]);
+        
+        assert_noop!(
+            Staking::register_validator(Origin::signed(40), 500, Perbill::zero()),
+            Error::<Test>::InvalidAccountType
+        );
+        assert_ok!(Staking::register_validator(Origin::signed(50), 500, Perbill::zero()));
+        assert!(Staking::validators(50).is_some());
+    });
+}
+
+#[test]
+fn eras_rotate_on_session_boundaries() {
+    ExtBuilder::default().build_and_execute(|| {
+        // Sessions 1 and 2 start at blocks 5 and 10; the second one ends era 0
+        start_next_era();
+        assert_eq!(System::block_number(), EraDuration::get());
+        assert_eq!(Session::current_index(), 2);
+        assert_eq!(Staking::era_start_block_number(1), EraDuration::get());
+        assert_eq!(Staking::eras_start_session_index(1), Some(3));
+        
+        // An era ending between two boundaries waits for the next one
+        assert_ok!(Staking::set_era_duration(Origin::root(), 7));
+        start_era(3);
+        assert_eq!(Staking::era_start_block_number(2), 20);
+        assert_eq!(Staking::era_start_block_number(3), 30);
+        assert_eq!(Session::current_index(), 6);
+        assert_eq!(Staking::eras_start_session_index(3), Some(7));
+    });
+}
+
+#[test]
+fn a_new_era_duration_applies_from_the_next_rotation() {
+    ExtBuilder::default().build_and_execute(|| {
+        start_next_era();
+        assert_eq!(System::block_number(), 10);
+        
+        // Era 1 keeps the duration it started with
+        assert_ok!(Staking::set_era_duration(Origin::root(), 5));
+        assert!(staking_events().contains(&StakingEvent::EraDurationSet(5)));
+        assert_eq!(Staking::current_era_duration(), EraDuration::get());
+        assert_eq!(Staking::next_era_duration(), Some(5));
+        start_next_era();
+        assert_eq!(Staking::era_start_block_number(2), 20);
+        
+        // Era 2 started with the new duration
+        assert_eq!(Staking::current_era_duration(), 5);
+        assert_eq!(Staking::next_era_duration(), None);
+        start_next_era();
+        assert_eq!(Staking::era_start_block_number(3), 25);
+        
+        assert_noop!(Staking::set_era_duration(Origin::root(), 4), Error::<Test>::InvalidEraDuration);
+    });
+}
+
+#[test]
+fn unclaimed_rewards_warn_before_expiry_and_are_swept_within_the_budget() {
+    ExtBuilder::default().max_rewards_swept(1).build_and_execute(|| {
+        start_era(1);
+        author_blocks(10, 1);
+        
+        // Era 1 is computed at the start of era 3, and expires as era 6 starts
+        start_era(4);
+        assert!(Staking::eras_validator_reward(1, 10).is_some());
+        assert!(!staking_events().contains(&StakingEvent::RewardExpiringSoon(1)));
+        
+        start_era(5);
+        assert!(staking_events().contains(&StakingEvent::RewardExpiringSoon(1)));
+        assert_eq!(Staking::earliest_unclaimed_era(), 1);
+        
+        // One entry per rotation: the reward first, then the points
+        start_era(6);
+        assert!(Staking::eras_validator_reward(1, 10).is_none());
+        assert!(ErasRewardPoints::<Test>::contains_key(1, 10));
+        assert_eq!(Staking::next_era_to_sweep(), 1);
+        assert_noop!(Staking::payout_stakers(Origin::signed(1), 10, 1), Error::<Test>::NoRewardsForEra);
+        
+        start_era(7);
+        assert!(!ErasRewardPoints::<Test>::contains_key(1, 10));
+        assert!(ErasStakers::<Test>::contains_key(1, 10));
+        assert_eq!(Staking::next_era_to_sweep(), 1);
+        
+        // The exposures and authored blocks follow, one entry per rotation
+        start_era(14);
+        assert_eq!(ErasStakers::<Test>::iter_prefix(1).count(), 0);
+        assert!(!ErasAuthoredBlocks::<Test>::contains_key(1, 10));
+        assert!(!ErasValidatorList::<Test>::contains_key(1));
+        assert!(Staking::next_era_to_sweep() > 1);
+    });
+}
+
+#[test]
+fn eras_falling_out_of_the_history_are_pruned() {
+    ExtBuilder::default().build_and_execute(|| {
+        start_era(1);
+        author_blocks(10, 1);
+        start_era(2);
+        author_blocks(10, 1);
+        assert!(ErasStakers::<Test>::contains_key(1, 10));
+        
+        // Era 1 falls out of the history as era `1 + HistoryDepth + 1` starts
+        start_era(1 + HistoryDepth::get());
+        assert!(ErasStakers::<Test>::contains_key(1, 10));
+        
+        start_era(2 + HistoryDepth::get());
+        assert_eq!(Staking::next_era_to_sweep(), 2);
+        assert_eq!(ErasStakers::<Test>::iter_prefix(1).count(), 0);
+        assert!(!ErasAuthoredBlocks::<Test>::contains_key(1, 10));
+        assert!(!ErasValidatorList::<Test>::contains_key(1));
+        assert!(!ErasTotalStake::<Test>::contains_key(1));
+        assert!(!ErasBlockCount::<Test>::contains_key(1));
+        assert!(!EraStartBlockNumber::<Test>::contains_key(1));
+        assert!(!ErasStartSessionIndex::<Test>::contains_key(1));
+        
+        // The next era is still within the history
+        assert!(ErasStakers::<Test>::contains_key(2, 10));
+        assert!(ErasAuthoredBlocks::<Test>::contains_key(2, 10));
+        assert!(ErasValidatorList::<Test>::contains_key(2));
+    });
+}
+
+#[test]
+fn reconcile_delegator_repairs_a_drifted_total() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::delegate(Origin::signed(1), 20, 50));
+        
+        Delegators::<Test>::mutate(1, |delegator| delegator.as_mut().unwrap().total_staked = 999);
+        
+        assert_ok!(Staking::reconcile_delegator(Origin::signed(2), 1));
+        assert_eq!(Staking::delegators(1).unwrap().total_staked, 150);
+        assert!(staking_events().contains(&StakingEvent::DelegatorReconciled(1, 999, 150)));
+        
+        // A consistent total is left alone
+        let events = System::events().len();
+        assert_ok!(Staking::reconcile_delegator(Origin::signed(2), 1));
+        assert_eq!(System::events().len(), events);
+        
+        assert_noop!(Staking::reconcile_delegator(Origin::signed(2), 3), Error::<Test>::NotDelegator);
+    });
+}
+
+#[test]
+fn delegations_beyond_the_stake_multiplier_wait_for_more_self_stake() {
+    ExtBuilder::default().build_and_execute(|| {
+        let cap = GENESIS_STAKE * MaxStakeMultiplier::get() as Balance;
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, cap - GENESIS_STAKE));
+        assert_eq!(Staking::validators(10).unwrap().total_stake, cap);
+        
+        assert_noop!(Staking::delegate(Origin::signed(2), 10, 100), Error::<Test>::SelfBondTooLow);
+        
+        // 20 more self-stake raises the cap by 200
+        assert_ok!(Staking::increase_stake(Origin::signed(10), 20));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 100));
+        assert_eq!(Staking::validators(10).unwrap().total_stake, cap + 120);
+    });
+}
+
+#[test]
+fn idle_and_unselected_reputation_decays_every_era() {
+    ExtBuilder::default().build_and_execute(|| {
+        let score = |who| Staking::validators(who).unwrap().reputation.score;
+        author_blocks(10, 3);
+        start_next_era();
+        let mut last = score(10);
+        assert!(last > 0);
+        
+        // Selected but idle, while 20 authors every block
+        for era in 2..=4 {
+            author_blocks(20, 3);
+            start_era(era);
+            assert!(score(10) < last);
+            last = score(10);
+        }
+        
+        // Left out of era 5 onwards while chilled, and decaying all the same
+        assert_ok!(Staking::chill(Origin::signed(10)));
+        for era in 5..=6 {
+            author_blocks(20, 3);
+            start_era(era);
+            assert!(!Staking::eras_validator_list(era).contains(&10));
+            assert!(score(10) < last);
+            last = score(10);
+        }
+    });
+}
+
+#[test]
+fn ties_at_the_selection_boundary_are_broken_by_the_random_seed() {
+    ExtBuilder::default()
+        .validators(vec![(10, 1_000), (20, 1_000), (30, 1_000), (40, 1_000), (50, 1_000)])
+        .build_and_execute(|| {
+            let sets: Vec<Vec<AccountId>> = (0..8u64)
+                .map(|seed| {
+                    RandomSeed::set(&seed);
+                    let mut set = Staking::select_validators().into_inner();
+                    assert_eq!(set.len() as u32, ValidatorsCount::get());
+                    set.sort();
+                    set
+                })
+                .collect();
+            
+            assert!(sets.iter().any(|set| *set != sets[0]));
+            
+            // The same seed always picks the same set
+            RandomSeed::set(&3);
+            let mut again = Staking::select_validators().into_inner();
+            again.sort();
+            assert_eq!(again, sets[3]);
+        });
+}
+
+#[test]
+fn validator_lists_decode_only_within_the_bound() {
+    ExtBuilder::default().build_and_execute(|| {
+        let within: Vec<AccountId> = (1..=MaxValidatorsCount::get() as u64).collect();
+        let beyond: Vec<AccountId> = (1..=MaxValidatorsCount::get() as u64 + 1).collect();
+        
+        // A plain `Vec` encodes like the `BoundedVec` that replaced it
+        frame_support::storage::unhashed::put(&ErasValidatorList::<Test>::hashed_key_for(7), &within);
+        assert_eq!(Staking::eras_validator_list(7).into_inner(), within);
+        
+        frame_support::storage::unhashed::put(&ErasValidatorList::<Test>::hashed_key_for(8), &beyond);
+        assert!(ErasValidatorList::<Test>::try_get(8).is_err());
+        
+        // Registration stops at `MaxCandidates`
+        ValidatorCount::<Test>::put(MaxCandidates::get());
+        assert_noop!(
+            Staking::register_validator(Origin::signed(40), 500, Perbill::zero()),
+            Error::<Test>::TooManyCandidates
+        );
+    });
+}
+
+#[test]
+fn oversubscription_rejects_or_evicts_the_smallest_delegation() {
+    ExtBuilder::default().max_nominators(2).build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 200));
+        assert_noop!(Staking::delegate(Origin::signed(3), 10, 500), Error::<Test>::DelegationsClosed);
+        
+        // A leaving delegator reopens the validator
+        start_next_era();
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 100));
+        assert!(Staking::validators(10).unwrap().delegations_open);
+        assert_ok!(Staking::delegate(Origin::signed(3), 10, 500));
+    });
+    
+    ExtBuilder::default()
+        .max_nominators(2)
+        .oversubscription(OversubscriptionPolicy::EvictSmallest)
+        .build_and_execute(|| {
+            assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+            assert_ok!(Staking::delegate(Origin::signed(2), 10, 200));
+            
+            // Not larger than the smallest delegation
+            assert_noop!(Staking::delegate(Origin::signed(3), 10, 100), Error::<Test>::TooManyDelegators);
+            
+            assert_ok!(Staking::delegate(Origin::signed(3), 10, 300));
+            assert!(staking_events().contains(&StakingEvent::DelegationEvicted(1, 10, 100)));
+            assert!(Staking::delegators(1).is_none());
+            assert_eq!(Balances::reserved_balance(1), 0);
+            assert_eq!(Staking::nominators_of(&10).len(), 2);
+            assert_eq!(Staking::validators(10).unwrap().total_stake, GENESIS_STAKE + 500);
+        });
+}
+
+#[test]
+fn a_partial_rebond_leaves_the_remainder_unbonding_with_its_original_era() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), 300));
+        start_next_era();
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), 200));
+        let first_unlock = BondingDuration::get();
+        assert_eq!(
+            Staking::unbonding_stake(10).into_inner(),
+            vec![(first_unlock, 300), (first_unlock + 1, 200)]
+        );
+        
+        // The newest chunk is rebonded whole, then 50 of the older one
+        assert_ok!(Staking::rebond(Origin::signed(10), 250));
+        
+        assert_eq!(Staking::unbonding_stake(10).into_inner(), vec![(first_unlock, 250)]);
+        let validator = Staking::validators(10).unwrap();
+        assert_eq!(validator.self_stake, GENESIS_STAKE - 250);
+        assert_eq!(validator.total_stake, GENESIS_STAKE - 250);
+        assert_eq!(stake_lock(10), GENESIS_STAKE);
+        assert!(staking_events().contains(&StakingEvent::Rebonded(10, 250)));
+    });
+}
+
+#[test]
+fn the_era_reward_is_recorded_at_rotation_and_consumed_by_distribution() {
+    ExtBuilder::default().build_and_execute(|| {
+        author_blocks(10, 10);
+        assert_eq!(Staking::eras_reward(0), None);
+        
+        start_era(1);
+        assert_eq!(Staking::eras_reward(0), Some(EraReward::get()));
+        assert!(Staking::eras_validator_reward(0, 10).is_none());
+        
+        // Distributing era 0 moves the whole reward into the per-validator entries
+        start_era(2);
+        assert_eq!(Staking::eras_reward(0), None);
+        assert_eq!(Staking::eras_validator_reward(0, 10).unwrap().amount, EraReward::get());
+    });
+}
+
+#[test]
+fn the_reward_of_an_era_without_points_goes_to_the_dust_handler() {
+    ExtBuilder::default().build_and_execute(|| {
+        start_era(1);
+        assert_eq!(Staking::eras_reward(0), Some(EraReward::get()));
+        
+        start_era(2);
+        assert_eq!(Staking::eras_reward(0), None);
+        assert_eq!(ErasValidatorReward::<Test>::iter_prefix(0).count(), 0);
+        assert_eq!(DustRemoved::get(), EraReward::get());
+    });
+}
+
+#[test]
+fn the_genesis_validators_are_selected_for_session_zero() {
+    ExtBuilder::default().validators(vec![(40, 500), (50, 800)]).build_and_execute(|| {
+        assert_eq!(Staking::eras_validator_list(0).into_inner(), vec![50, 40]);
+        assert_eq!(Staking::validator_status(40), ValidatorStatus::Active);
+        assert_eq!(stake_lock(50), 800);
+        
+        let mut session_zero = Session::validators();
+        session_zero.sort();
+        assert_eq!(session_zero, vec![40, 50]);
+    });
+}
+
+#[test]
+#[should_panic(expected = "Initial validators must not contain duplicates")]
+fn a_duplicate_genesis_validator_is_rejected() {
+    ExtBuilder::default().validators(vec![(10, GENESIS_STAKE), (10, GENESIS_STAKE)]).build();
+}
+
+#[test]
+#[should_panic(expected = "Initial validator stake is below the minimum validator stake")]
+fn a_genesis_stake_below_the_minimum_is_rejected() {
+    ExtBuilder::default().validators(vec![(10, MinValidatorStake::get() - 1)]).build();
+}
+
+#[test]
+fn pending_reward_matches_what_payout_stakers_pays() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 3_000));
+        start_era(1);
+        author_blocks(10, 10);
+        start_era(3);
+        
+        let validator_pending = Staking::pending_reward(&10, 1).unwrap();
+        let delegator_pending = Staking::pending_reward(&1, 1).unwrap();
+        assert!(delegator_pending > validator_pending);
+        assert_eq!(Staking::pending_reward(&2, 1), None);
+        
+        let (validator_before, delegator_before) = (Balances::free_balance(10), Balances::free_balance(1));
+        assert_ok!(Staking::payout_stakers(Origin::signed(2), 10, 1));
+        
+        assert_eq!(Balances::free_balance(10), validator_before + validator_pending);
+        assert_eq!(Balances::free_balance(1), delegator_before + delegator_pending);
+        // Nothing is left to claim once paid
+        assert_eq!(Staking::pending_reward(&10, 1), None);
+        assert_eq!(Staking::pending_reward(&1, 1), None);
+    });
+}
+
+#[test]
+fn heavy_delegation_below_the_self_stake_ratio_drops_the_validator_from_the_set() {
+    ExtBuilder::default().min_self_stake_ratio(Perbill::from_percent(20)).build_and_execute(|| {
+        // 1000 of 5000 still meets the ratio
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 4_000));
+        start_next_era();
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Active);
+        assert!(Staking::eras_validator_list(1).contains(&10));
+        
+        // 1000 of 5500 does not
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 500));
+        assert_eq!(Staking::effective_status(&10), ValidatorStatus::InsufficientStake);
+        start_next_era();
+        
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::InsufficientStake);
+        let mut selected = Staking::eras_validator_list(2).into_inner();
+        selected.sort();
+        assert_eq!(selected, vec![20, 30]);
+    });
+}
+
+#[test]
+fn a_partial_undelegation_is_released_only_at_maturity() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 300));
+        assert_ok!(Staking::delegate(Origin::signed(1), 20, 200));
+        start_next_era();
+        
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 100));
+        let unlock_era = Staking::current_era() + BondingDuration::get();
+        assert_eq!(Staking::unbonding_delegations(1).into_inner(), vec![(unlock_era, 100)]);
+        assert_eq!(Balances::reserved_balance(1), 500);
+        
+        start_era(unlock_era - 1);
+        assert_noop!(
+            Staking::withdraw_unbonded(Origin::signed(1)),
+            Error::<Test>::CannotWithdrawBeforeBondingPeriod
+        );
+        
+        start_era(unlock_era);
+        assert_ok!(Staking::withdraw_unbonded(Origin::signed(1)));
+        
+        // Only the matured chunk is released; both delegations stay reserved
+        assert_eq!(Balances::reserved_balance(1), 400);
+        assert!(Staking::unbonding_delegations(1).is_empty());
+        let delegator = Staking::delegators(1).unwrap();
+        assert_eq!(delegator.total_staked, 400);
+        assert_eq!(delegator.delegations.get(&10).unwrap().amount, 200);
+    });
+}
+
+#[test]
+fn increasing_stake_twice_locks_the_cumulative_self_stake() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::increase_stake(Origin::signed(10), 200));
+        assert_ok!(Staking::increase_stake(Origin::signed(10), 300));
+        
+        assert_eq!(Staking::validators(10).unwrap().self_stake, GENESIS_STAKE + 500);
+        assert_eq!(stake_lock(10), GENESIS_STAKE + 500);
+    });
+}
+
+#[test]
+fn validator_metadata_is_bounded_and_removed_with_the_validator() {
+    use codec::{Decode, Encode};
+    
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::set_validator_metadata(Origin::signed(10), metadata(b"alpha")));
+        assert_eq!(Staking::validator_metadata(10), Some(metadata(b"alpha")));
+        assert_ok!(Staking::set_validator_metadata(Origin::signed(10), metadata(b"beta")));
+        assert_eq!(Staking::validator_metadata(10), Some(metadata(b"beta")));
+        assert!(staking_events().contains(&StakingEvent::ValidatorMetadataUpdated(10)));
+        assert_noop!(
+            Staking::set_validator_metadata(Origin::signed(1), metadata(b"gamma")),
+            Error::<Test>::NotValidator
+        );
+        
+        // A name over `MaxValidatorNameLength` does not even decode
+        let oversized = (vec![0u8; 17], Vec::<u8>::new(), Vec::<u8>::new()).encode();
+        assert!(ValidatorDetailsOf::<Test>::decode(&mut &oversized[..]).is_err());
+        
+        // Leaving by decreasing the self-stake to zero drops the metadata
+        assert_ok!(Staking::deregister_validator(Origin::signed(10), false));
+        assert!(Staking::validator_metadata(10).is_some());
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), GENESIS_STAKE));
+        assert_eq!(Staking::validator_metadata(10), None);
+        
+        // So does being reaped
+        assert_ok!(Staking::set_validator_metadata(Origin::signed(20), metadata(b"delta")));
+        assert_ok!(Staking::slash_validator(Origin::root(), 20, Perbill::one()));
+        assert_ok!(Staking::deregister_validator(Origin::signed(20), false));
+        start_era(BondingDuration::get() + 1);
+        assert_ok!(Staking::reap_stale_validator(Origin::signed(1), 20));
+        assert_eq!(Staking::validator_metadata(20), None);
+    });
+}
+
+#[test]
+fn delegating_to_a_slashed_validator_fails() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::slash_validator(Origin::root(), 10, Perbill::from_percent(10)));
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Slashed);
+        
+        assert_noop!(Staking::delegate(Origin::signed(1), 10, 100), Error::<Test>::ValidatorNotActive);
+        assert_ok!(Staking::delegate(Origin::signed(1), 20, 100));
+        assert_eq!(Staking::validators(20).unwrap().total_stake, GENESIS_STAKE + 100);
+    });
+}
+
+#[test]
+fn a_batch_with_one_bad_target_delegates_nothing() {
+    ExtBuilder::default().build_and_execute(|| {
+        let targets: BoundedVec<_, MaxDelegationsPerDelegator> =
+            vec![(10, 100), (99, 100), (20, 100)].try_into().unwrap();
+        assert_noop!(Staking::delegate_batch(Origin::signed(1), targets), Error::<Test>::NotValidator);
+        assert!(Staking::delegators(1).is_none());
+        assert_eq!(Balances::reserved_balance(1), 0);
+        
+        let targets: BoundedVec<_, MaxDelegationsPerDelegator> =
+            vec![(10, 100), (20, 100)].try_into().unwrap();
+        assert_ok!(Staking::delegate_batch(Origin::signed(1), targets));
+        assert_eq!(Staking::delegators(1).unwrap().total_staked, 200);
+    });
+}
+
+#[test]
+fn more_reward_points_earn_a_larger_share_of_the_era_reward() {
+    ExtBuilder::default().build_and_execute(|| {
+        author_blocks(10, 3);
+        author_blocks(20, 1);
+        assert!(Staking::eras_reward_points(0, 10) > Staking::eras_reward_points(0, 20));
+        
+        start_era(2);
+        
+        let reward = |validator| Staking::eras_validator_reward(0, validator).unwrap().amount;
+        assert!(reward(10) > reward(20));
+        assert!(reward(20) > 0);
+        // A validator that authored nothing earns nothing
+        assert!(Staking::eras_validator_reward(0, 30).is_none());
+    });
+}
+
+#[test]
+fn a_deferred_slash_applies_at_its_era_unless_cancelled() {
+    ExtBuilder::default().slash_defer_duration(2).build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 500));
+        start_next_era();
+        
+        assert_ok!(Staking::slash_validator(Origin::root(), 10, Perbill::from_percent(10)));
+        assert_ok!(Staking::slash_validator(Origin::root(), 20, Perbill::from_percent(10)));
+        assert!(staking_events().contains(&StakingEvent::SlashDeferred(10, Perbill::from_percent(10), 3)));
+        assert_ok!(Staking::cancel_deferred_slash(Origin::root(), 3, 20));
+        assert_noop!(
+            Staking::cancel_deferred_slash(Origin::root(), 3, 20),
+            Error::<Test>::NoUnappliedSlash
+        );
+        
+        // Unbonding does not escape the pending slash, and nothing can be withdrawn meanwhile
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 500));
+        assert_noop!(Staking::withdraw_unbonded(Origin::signed(1)), Error::<Test>::SlashPending);
+        assert_noop!(Staking::deregister_validator(Origin::signed(10), false), Error::<Test>::SlashPending);
+        
+        start_era(2);
+        assert_eq!(Staking::validators(10).unwrap().self_stake, GENESIS_STAKE);
+        
+        start_era(3);
+        assert_eq!(Staking::validators(10).unwrap().self_stake, GENESIS_STAKE - 100);
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Slashed);
+        assert_eq!(stake_lock(10), GENESIS_STAKE - 100);
+        // The delegation had left for the unbonding queue, which is slashed instead
+        assert_eq!(Staking::unbonding_delegations(1).into_inner(), vec![(3, 450)]);
+        assert_eq!(Balances::reserved_balance(1), 450);
+        assert!(staking_events().contains(&StakingEvent::ValidatorSlashed(10, 150)));
+        
+        // The cancelled slash never applies
+        assert_eq!(Staking::validators(20).unwrap().self_stake, GENESIS_STAKE);
+        assert_eq!(Staking::validator_status(20), ValidatorStatus::Active);
+        
+        assert_ok!(Staking::withdraw_unbonded(Origin::signed(1)));
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn kicking_one_delegator_leaves_the_other_delegation_untouched() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 300));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 200));
+        
+        assert_ok!(Staking::kick_delegator(Origin::signed(10), 1));
+        
+        assert!(Staking::delegators(1).is_none());
+        assert_eq!(
+            Staking::unbonding_delegations(1).into_inner(),
+            vec![(BondingDuration::get(), 300)]
+        );
+        assert_eq!(Staking::nominators_of(&10), vec![(2, 200)]);
+        assert_eq!(Staking::delegators(2).unwrap().delegations.get(&10).unwrap().amount, 200);
+        assert_eq!(Staking::validators(10).unwrap().total_stake, GENESIS_STAKE + 200);
+        assert_noop!(Staking::kick_delegator(Origin::signed(10), 1), Error::<Test>::NotDelegator);
+    });
+}
+
+#[test]
+fn staking_overview_reports_an_account_that_validates_and_delegates() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(10), 20, 200));
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 400));
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), 100));
+        
+        let overview = Staking::staking_overview(&10);
+        assert!(overview.is_validator);
+        assert_eq!(overview.self_stake, GENESIS_STAKE - 100);
+        assert_eq!(overview.total_backing, GENESIS_STAKE - 100 + 400);
+        assert_eq!(overview.delegations, vec![(20, 200)]);
+        assert_eq!(overview.unbonding_stake, vec![(BondingDuration::get(), 100)]);
+        assert!(overview.unbonding_delegations.is_empty());
+        
+        // A plain delegator has no validator side
+        let overview = Staking::staking_overview(&1);
+        assert!(!overview.is_validator);
+        assert_eq!(overview.self_stake, 0);
+        assert_eq!(overview.delegations, vec![(10, 400)]);
+    });
+}
+
+#[test]
+fn deregistering_every_validator_keeps_the_last_set_without_the_slashed() {
+    ExtBuilder::default().build_and_execute(|| {
+        start_era(1);
+        let last_set = Staking::eras_validator_list(1);
+        assert_eq!(last_set.len(), 3);
+        
+        for validator in [10, 20, 30] {
+            assert_ok!(Staking::deregister_validator(Origin::signed(validator), false));
+        }
+        start_next_era();
+        assert_eq!(Staking::eras_validator_list(2), last_set);
+        assert!(staking_events().contains(&StakingEvent::ValidatorSetRetained(2)));
+        
+        // A slashed validator is dropped from the retained set
+        assert_ok!(Staking::slash_validator(Origin::root(), 10, Perbill::from_percent(10)));
+        start_next_era();
+        let retained = Staking::eras_validator_list(3);
+        assert_eq!(retained.len(), 2);
+        assert!(!retained.contains(&10));
+        assert!(staking_events().contains(&StakingEvent::ValidatorSetRetained(3)));
+    });
+}
+
+#[test]
+fn the_stake_lock_always_covers_the_self_stake_and_its_unbonding_chunks() {
+    // Any sequence of stake operations, successful or not, keeps the `stakeatls` lock equal to
+    // what `withdraw_unbonded` has not released yet
+    for seed in 1..=32u64 {
+        ExtBuilder::default().build_and_execute(|| {
+            // xorshift64, so that every failing sequence can be replayed from its seed
+            let mut state = seed;
+            let mut next = |bound: u64| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state % bound
+            };
+            
+            for step in 0..40 {
+                let amount = next(400) as Balance + 1;
+                let _ = match next(5) {
+                    0 => Staking::increase_stake(Origin::signed(10), amount),
+                    1 => Staking::decrease_stake(Origin::signed(10), amount),
+                    2 => Staking::rebond(Origin::signed(10), amount),
+                    3 => Staking::withdraw_unbonded(Origin::signed(10)),
+                    _ => {
+                        start_next_era();
+                        Ok(())
+                    },
+                };
+                
+                let self_stake = Staking::validators(10).map_or(0, |validator| validator.self_stake);
+                let unbonding: Balance =
+                    Staking::unbonding_stake(10).iter().map(|(_, amount)| amount).sum();
+                assert_eq!(
+                    stake_lock(10),
+                    self_stake + unbonding,
+                    "seed {} diverged at step {}",
+                    seed,
+                    step
+                );
+            }
+        });
+    }
+}
+
+#[test]
+fn force_new_era_rotates_on_the_next_block() {
+    ExtBuilder::default().build_and_execute(|| {
+        run_to_block(2);
+        assert_noop!(Staking::force_new_era(Origin::signed(1)), BadOrigin);
+        assert_ok!(Staking::force_new_era(Origin::root()));
+        assert_eq!(Staking::current_era(), 0);
+        
+        run_to_block(3);
+        assert_eq!(Staking::current_era(), 1);
+        assert_eq!(Staking::era_start_block_number(1), 3);
+        assert_eq!(Staking::force_era(), Forcing::NotForcing);
+        assert!(staking_events().contains(&StakingEvent::NewEra(1)));
+        
+        // The forced era's set is handed to the session planned at the next boundary
+        run_to_block(5);
+        assert_eq!(Staking::eras_start_session_index(1), Some(2));
+        
+        // Rotation is back on `CurrentEraDuration`, counted from the forced era's start
+        start_next_era();
+        assert_eq!(Staking::era_start_block_number(2), 15);
+        
+        assert_ok!(Staking::force_no_era(Origin::root()));
+        run_to_block(60);
+        assert_eq!(Staking::current_era(), 2);
+    });
+}
+
+#[test]
+fn a_compounding_delegation_grows_up_to_the_stake_cap_while_a_cash_one_is_paid() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 4_500));
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 4_000));
+        assert_ok!(Staking::set_compound(Origin::signed(1), 10, true));
+        start_era(1);
+        author_blocks(10, 10);
+        start_era(2);
+        author_blocks(10, 10);
+        start_era(4);
+        let delegation = |who| Staking::delegators(who).unwrap().delegations.get(&10).unwrap().amount;
+        
+        // Era 1: the compounding delegation grows, the other one is paid in free balance
+        let compounding_reward = Staking::pending_reward(&1, 1).unwrap();
+        let cash_reward = Staking::pending_reward(&2, 1).unwrap();
+        let (compounding_free, cash_free) = (Balances::free_balance(1), Balances::free_balance(2));
+        assert_ok!(Staking::payout_stakers(Origin::signed(3), 10, 1));
+        
+        assert_eq!(delegation(1), 4_500 + compounding_reward);
+        assert_eq!(Balances::free_balance(1), compounding_free);
+        assert_eq!(delegation(2), 4_000);
+        assert_eq!(Balances::free_balance(2), cash_free + cash_reward);
+        
+        // Era 2: only the headroom under `max_total_stake` is compounded, the rest is paid out
+        let reward = Staking::pending_reward(&1, 2).unwrap();
+        let (before, free) = (delegation(1), Balances::free_balance(1));
+        assert_ok!(Staking::payout_stakers(Origin::signed(3), 10, 2));
+        
+        let validator = Staking::validators(10).unwrap();
+        assert_eq!(validator.total_stake, Staking::max_total_stake(validator.self_stake));
+        let compounded = delegation(1) - before;
+        assert!(compounded < reward);
+        assert_eq!(Balances::free_balance(1), free + reward - compounded);
+        assert_eq!(Staking::delegators(1).unwrap().total_staked, delegation(1));
+    });
+}
+
+#[test]
+fn a_chilled_validator_keeps_its_delegations_and_withdraws_everything_only_once_left() {
+    ExtBuilder::default().build_and_execute(|| {
+        assert_ok!(Staking::delegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::chill(Origin::signed(10)));
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Chilled);
+        assert_noop!(Staking::delegate(Origin::signed(2), 10, 100), Error::<Test>::ValidatorNotActive);
+        
+        // Chilling is no way around deregistering
+        assert_noop!(
+            Staking::decrease_stake(Origin::signed(10), GENESIS_STAKE),
+            Error::<Test>::CannotWithdrawWhileActive
+        );
+        start_next_era();
+        assert!(!Staking::eras_validator_list(1).contains(&10));
+        assert_eq!(Staking::delegators(1).unwrap().delegations.get(&10).unwrap().amount, 100);
+        
+        assert_ok!(Staking::reactivate(Origin::signed(10)));
+        assert_noop!(Staking::reactivate(Origin::signed(10)), Error::<Test>::NotChilled);
+        assert_eq!(Staking::validator_status(10), ValidatorStatus::Active);
+        assert_ok!(Staking::delegate(Origin::signed(2), 10, 100));
+        start_next_era();
+        assert!(Staking::eras_validator_list(2).contains(&10));
+        
+        // A deregistered validator keeps its record until nobody delegates to it
+        let candidates = Staking::validator_count();
+        assert_ok!(Staking::deregister_validator(Origin::signed(10), false));
+        assert_eq!(Staking::validator_count(), candidates - 1);
+        assert_noop!(
+            Staking::decrease_stake(Origin::signed(10), GENESIS_STAKE),
+            Error::<Test>::ValidatorHasDelegations
+        );
+        assert_ok!(Staking::undelegate(Origin::signed(1), 10, 100));
+        assert_ok!(Staking::undelegate(Origin::signed(2), 10, 100));
+        assert_ok!(Staking::decrease_stake(Origin::signed(10), GENESIS_STAKE));
+        assert!(Staking::validators(10).is_none());
+        assert_eq!(Staking::validator_count(), candidates - 1);
+    });
+}
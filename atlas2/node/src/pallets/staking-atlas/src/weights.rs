@@ -0,0 +1,176 @@
+//! Weights for the staking atlas pallet.
+//!
+//! These are placeholders until the benchmarks in `benchmarking.rs` have been run on reference
+//! hardware with the node's `benchmark pallet` subcommand, whose output replaces this file.
+//! The database component of each weight counts the reads and writes the extrinsic performs;
+//! the execution component is a deliberately generous estimate, not a measurement.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{constants::RocksDbWeight, Weight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for the staking atlas pallet.
+pub trait WeightInfo {
+    fn register_validator() -> Weight;
+    fn delegate(d: u32) -> Weight;
+    fn delegate_batch(n: u32, d: u32) -> Weight;
+    fn undelegate() -> Weight;
+    fn increase_stake() -> Weight;
+    fn decrease_stake() -> Weight;
+    fn payout_stakers(n: u32) -> Weight;
+    fn claim_all(n: u32) -> Weight;
+    fn slash_validator(n: u32) -> Weight;
+    fn set_compound() -> Weight;
+    fn new_era(c: u32, n: u32) -> Weight;
+}
+
+/// The estimated execution time of an extrinsic, before any per-item cost.
+const BASE: u64 = 50_000_000;
+
+/// The estimated execution time of each item an extrinsic iterates over.
+const PER_ITEM: u64 = 10_000_000;
+
+/// The delegators per payout assumed by `claim_all` in the `()` implementation, which has no
+/// runtime to read `MaxNominatorsPerValidator` from.
+const CLAIM_ALL_DELEGATORS: u32 = 64;
+
+/// Weights for the staking atlas pallet using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: crate::Config> WeightInfo for SubstrateWeight<T> {
+    fn register_validator() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(4))
+    }
+    fn delegate(d: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(d as u64))
+            .saturating_add(T::DbWeight::get().reads(7))
+            .saturating_add(T::DbWeight::get().writes(4))
+    }
+    fn delegate_batch(n: u32, d: u32) -> Weight {
+        Self::delegate(d).saturating_mul(n as u64)
+    }
+    fn undelegate() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(T::DbWeight::get().reads(5))
+            .saturating_add(T::DbWeight::get().writes(4))
+    }
+    fn increase_stake() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(T::DbWeight::get().reads(4))
+            .saturating_add(T::DbWeight::get().writes(2))
+    }
+    fn decrease_stake() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(T::DbWeight::get().reads(6))
+            .saturating_add(T::DbWeight::get().writes(3))
+    }
+    fn payout_stakers(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(n as u64))
+            .saturating_add(T::DbWeight::get().reads(5))
+            .saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+            .saturating_add(T::DbWeight::get().writes(2))
+            .saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(n as u64)))
+    }
+    fn claim_all(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(T::DbWeight::get().reads(2))
+            .saturating_add(Self::payout_stakers(T::MaxNominatorsPerValidator::get()).saturating_mul(n as u64))
+    }
+    fn slash_validator(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(n as u64))
+            .saturating_add(T::DbWeight::get().reads(7))
+            .saturating_add(T::DbWeight::get().reads((3 as u64).saturating_mul(n as u64)))
+            .saturating_add(T::DbWeight::get().writes(5))
+            .saturating_add(T::DbWeight::get().writes((3 as u64).saturating_mul(n as u64)))
+    }
+    fn set_compound() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(T::DbWeight::get().reads(1))
+            .saturating_add(T::DbWeight::get().writes(1))
+    }
+    fn new_era(c: u32, n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(c as u64))
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(c as u64).saturating_mul(n as u64))
+            .saturating_add(T::DbWeight::get().reads(12))
+            .saturating_add(T::DbWeight::get().reads((4 as u64).saturating_mul(c as u64)))
+            .saturating_add(T::DbWeight::get().reads((c as u64).saturating_mul(n as u64)))
+            .saturating_add(T::DbWeight::get().writes(10))
+            .saturating_add(T::DbWeight::get().writes((3 as u64).saturating_mul(c as u64)))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn register_validator() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(4))
+    }
+    fn delegate(d: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(d as u64))
+            .saturating_add(RocksDbWeight::get().reads(7))
+            .saturating_add(RocksDbWeight::get().writes(4))
+    }
+    fn delegate_batch(n: u32, d: u32) -> Weight {
+        Self::delegate(d).saturating_mul(n as u64)
+    }
+    fn undelegate() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(RocksDbWeight::get().reads(5))
+            .saturating_add(RocksDbWeight::get().writes(4))
+    }
+    fn increase_stake() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(RocksDbWeight::get().reads(4))
+            .saturating_add(RocksDbWeight::get().writes(2))
+    }
+    fn decrease_stake() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(RocksDbWeight::get().reads(6))
+            .saturating_add(RocksDbWeight::get().writes(3))
+    }
+    fn payout_stakers(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(n as u64))
+            .saturating_add(RocksDbWeight::get().reads(5))
+            .saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+            .saturating_add(RocksDbWeight::get().writes(2))
+            .saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(n as u64)))
+    }
+    fn claim_all(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(RocksDbWeight::get().reads(2))
+            .saturating_add(Self::payout_stakers(CLAIM_ALL_DELEGATORS).saturating_mul(n as u64))
+    }
+    fn slash_validator(n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(n as u64))
+            .saturating_add(RocksDbWeight::get().reads(7))
+            .saturating_add(RocksDbWeight::get().reads((3 as u64).saturating_mul(n as u64)))
+            .saturating_add(RocksDbWeight::get().writes(5))
+            .saturating_add(RocksDbWeight::get().writes((3 as u64).saturating_mul(n as u64)))
+    }
+    fn set_compound() -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(RocksDbWeight::get().reads(1))
+            .saturating_add(RocksDbWeight::get().writes(1))
+    }
+    fn new_era(c: u32, n: u32) -> Weight {
+        Weight::from_parts(BASE, 0)
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(c as u64))
+            .saturating_add(Weight::from_parts(PER_ITEM, 0).saturating_mul(c as u64).saturating_mul(n as u64))
+            .saturating_add(RocksDbWeight::get().reads(12))
+            .saturating_add(RocksDbWeight::get().reads((4 as u64).saturating_mul(c as u64)))
+            .saturating_add(RocksDbWeight::get().reads((c as u64).saturating_mul(n as u64)))
+            .saturating_add(RocksDbWeight::get().writes(10))
+            .saturating_add(RocksDbWeight::get().writes((3 as u64).saturating_mul(c as u64)))
+    }
+}